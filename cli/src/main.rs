@@ -0,0 +1,202 @@
+use std::{
+	fs::File,
+	io::{BufWriter, Error, ErrorKind, Result, Write},
+	path::{Path, PathBuf},
+};
+
+use codegen_luau::Options;
+use wasm_ast::module::{Module, TypeInfo};
+
+static USAGE: &str = "usage: wasm2luau [options] <file>
+
+options:
+	-b, --backend <luau|luajit>      language to translate to, `luau` by default
+	-r, --runtime <inline|separate>  write the runtime into the output or next to it
+	-o, --output <file>              write to a file instead of the standard output";
+
+#[derive(Clone, Copy)]
+enum Backend {
+	Luau,
+	LuaJIT,
+}
+
+impl Backend {
+	const fn extension(self) -> &'static str {
+		match self {
+			Self::Luau => "luau",
+			Self::LuaJIT => "lua",
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Runtime {
+	Inline,
+	Separate,
+}
+
+struct Arguments {
+	input: PathBuf,
+	output: Option<PathBuf>,
+	backend: Backend,
+	runtime: Runtime,
+}
+
+fn invalid_input(message: String) -> Error {
+	Error::new(ErrorKind::InvalidInput, message)
+}
+
+impl Arguments {
+	fn parse() -> Result<Self> {
+		let mut arguments = std::env::args().skip(1);
+		let mut input = None;
+		let mut output = None;
+		let mut backend = Backend::Luau;
+		let mut runtime = Runtime::Inline;
+
+		while let Some(name) = arguments.next() {
+			let mut value = || {
+				arguments
+					.next()
+					.ok_or_else(|| invalid_input(format!("missing value for `{name}`")))
+			};
+
+			match name.as_str() {
+				"-b" | "--backend" => {
+					backend = match value()?.as_str() {
+						"luau" => Backend::Luau,
+						"luajit" => Backend::LuaJIT,
+						other => return Err(invalid_input(format!("unknown backend `{other}`"))),
+					};
+				}
+				"-r" | "--runtime" => {
+					runtime = match value()?.as_str() {
+						"inline" => Runtime::Inline,
+						"separate" => Runtime::Separate,
+						other => return Err(invalid_input(format!("unknown runtime `{other}`"))),
+					};
+				}
+				"-o" | "--output" => output = Some(value()?.into()),
+				_ if input.is_none() && !name.starts_with('-') => input = Some(name.into()),
+				_ => return Err(invalid_input(format!("unexpected argument `{name}`"))),
+			}
+		}
+
+		let input = input.ok_or_else(|| invalid_input("missing input file".to_string()))?;
+
+		// The separate runtime is written beside the output, so it needs a place.
+		if runtime == Runtime::Separate && output.is_none() {
+			return Err(invalid_input(
+				"a separate runtime needs an `--output` file".to_string(),
+			));
+		}
+
+		Ok(Self {
+			input,
+			output,
+			backend,
+			runtime,
+		})
+	}
+
+	// The runtime of `out/name.luau` goes to `out/name_runtime.luau`, which is
+	// also the name the output requires it by.
+	fn runtime_path(&self) -> Option<(PathBuf, String)> {
+		let output = self.output.as_deref()?;
+		let stem = output.file_stem()?.to_string_lossy();
+		let name = format!("{stem}_runtime");
+		let path = output.with_file_name(format!("{name}.{}", self.backend.extension()));
+
+		Some((path, name))
+	}
+}
+
+fn create_file(path: &Path) -> Result<BufWriter<File>> {
+	File::create(path).map(BufWriter::new)
+}
+
+fn write_luau(wasm: &Module, arguments: &Arguments, w: &mut dyn Write) -> Result<()> {
+	let type_info = TypeInfo::from_module(wasm);
+	let mut options = Options::default();
+
+	match arguments.runtime_path() {
+		Some((path, name)) if arguments.runtime == Runtime::Separate => {
+			let mut runtime = create_file(&path)?;
+
+			codegen_luau::write_runtime_module(&mut runtime)?;
+			runtime.flush()?;
+
+			options.runtime_require = Some(format!("\"./{name}\""));
+
+			codegen_luau::write_header(&options, w)?;
+		}
+		_ => {
+			codegen_luau::write_header(&options, w)?;
+			writeln!(w, "{}", codegen_luau::RUNTIME)?;
+		}
+	}
+
+	let stats = codegen_luau::from_module_with_stats(wasm, &type_info, &options, w)?;
+
+	for warning in stats.warning_list() {
+		eprintln!("warning: {warning}");
+	}
+
+	Ok(())
+}
+
+fn write_luajit(wasm: &Module, arguments: &Arguments, w: &mut dyn Write) -> Result<()> {
+	let runtime = codegen_luajit::RUNTIME;
+
+	match arguments.runtime_path() {
+		Some((path, name)) if arguments.runtime == Runtime::Separate => {
+			let mut file = create_file(&path)?;
+
+			write!(file, "{runtime}")?;
+			file.flush()?;
+
+			writeln!(w, "local rt = require(\"{name}\")")?;
+		}
+		_ => {
+			writeln!(w, "local rt = (function()")?;
+			write!(w, "{runtime}")?;
+			writeln!(w, "end)()")?;
+		}
+	}
+
+	codegen_luajit::from_module_untyped(wasm, w)
+}
+
+fn run(arguments: &Arguments) -> Result<()> {
+	let data = std::fs::read(&arguments.input)?;
+
+	// Text is accepted alongside binary when the parser for it is built in.
+	#[cfg(feature = "wat")]
+	let data = wat::parse_bytes(&data)
+		.map_err(|e| Error::new(ErrorKind::InvalidData, e))?
+		.into_owned();
+
+	let wasm = Module::try_from_data(&data).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+	let mut w: Box<dyn Write> = match &arguments.output {
+		Some(path) => Box::new(create_file(path)?),
+		None => Box::new(std::io::stdout().lock()),
+	};
+
+	match arguments.backend {
+		Backend::Luau => write_luau(&wasm, arguments, &mut w)?,
+		Backend::LuaJIT => write_luajit(&wasm, arguments, &mut w)?,
+	}
+
+	w.flush()
+}
+
+fn main() -> Result<()> {
+	let arguments = Arguments::parse().map_err(|e| {
+		eprintln!("{e}\n\n{USAGE}\n");
+
+		Error::from(e.kind())
+	})?;
+
+	run(&arguments)
+}
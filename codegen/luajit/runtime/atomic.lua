@@ -0,0 +1,38 @@
+do
+	local atomic = {}
+
+	-- LuaJIT runs a single thread, so atomics behave as the plain load and store
+	-- of their width, which the generated code passes in along with the operation.
+	local scratch = module.allocator.new(1, 1)
+
+	function atomic.rmw(memory, addr, value, load, store, op)
+		local old = load(memory, addr)
+
+		store(memory, addr, op(old, value))
+
+		return old
+	end
+
+	function atomic.xchg(memory, addr, value, load, store)
+		local old = load(memory, addr)
+
+		store(memory, addr, value)
+
+		return old
+	end
+
+	function atomic.cmpxchg(memory, addr, expected, replacement, load, store)
+		local old = load(memory, addr)
+
+		-- The round trip wraps `expected` to the accessed width, as the comparison needs.
+		store(scratch, 0, expected)
+
+		if old == load(scratch, 0) then
+			store(memory, addr, replacement)
+		end
+
+		return old
+	end
+
+	module.atomic = atomic
+end
@@ -0,0 +1,181 @@
+do
+	local clz = {}
+	local ctz = {}
+	local popcnt = {}
+
+	local bit_and = bit.band
+	local bit_lshift = bit.lshift
+	local bit_rshift = bit.rshift
+
+	function clz.i32(num)
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		if bit_rshift(num, 16) == 0 then
+			num = bit_lshift(num, 16)
+			count = count + 16
+		end
+
+		if bit_rshift(num, 24) == 0 then
+			num = bit_lshift(num, 8)
+			count = count + 8
+		end
+
+		if bit_rshift(num, 28) == 0 then
+			num = bit_lshift(num, 4)
+			count = count + 4
+		end
+
+		if bit_rshift(num, 30) == 0 then
+			num = bit_lshift(num, 2)
+			count = count + 2
+		end
+
+		if bit_rshift(num, 31) == 0 then
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function ctz.i32(num)
+		if num == 0 then
+			return 32
+		end
+
+		local count = 0
+
+		if bit_lshift(num, 16) == 0 then
+			num = bit_rshift(num, 16)
+			count = count + 16
+		end
+
+		if bit_lshift(num, 24) == 0 then
+			num = bit_rshift(num, 8)
+			count = count + 8
+		end
+
+		if bit_lshift(num, 28) == 0 then
+			num = bit_rshift(num, 4)
+			count = count + 4
+		end
+
+		if bit_lshift(num, 30) == 0 then
+			num = bit_rshift(num, 2)
+			count = count + 2
+		end
+
+		if bit_lshift(num, 31) == 0 then
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function popcnt.i32(num)
+		local count = 0
+
+		while num ~= 0 do
+			num = bit_and(num, num - 1)
+			count = count + 1
+		end
+
+		return count
+	end
+
+	function clz.i64(num)
+		if num == 0 then
+			return 64 * NUM_ONE
+		end
+
+		local count = NUM_ZERO
+
+		if bit_rshift(num, 32) == NUM_ZERO then
+			num = bit_lshift(num, 32)
+			count = count + 32
+		end
+
+		if bit_rshift(num, 48) == NUM_ZERO then
+			num = bit_lshift(num, 16)
+			count = count + 16
+		end
+
+		if bit_rshift(num, 56) == NUM_ZERO then
+			num = bit_lshift(num, 8)
+			count = count + 8
+		end
+
+		if bit_rshift(num, 60) == NUM_ZERO then
+			num = bit_lshift(num, 4)
+			count = count + 4
+		end
+
+		if bit_rshift(num, 62) == NUM_ZERO then
+			num = bit_lshift(num, 2)
+			count = count + 2
+		end
+
+		if bit_rshift(num, 63) == NUM_ZERO then
+			count = count + NUM_ONE
+		end
+
+		return count
+	end
+
+	function ctz.i64(num)
+		if num == 0 then
+			return 64 * NUM_ONE
+		end
+
+		local count = NUM_ZERO
+
+		if bit_lshift(num, 32) == NUM_ZERO then
+			num = bit_rshift(num, 32)
+			count = count + 32
+		end
+
+		if bit_lshift(num, 48) == NUM_ZERO then
+			num = bit_rshift(num, 16)
+			count = count + 16
+		end
+
+		if bit_lshift(num, 56) == NUM_ZERO then
+			num = bit_rshift(num, 8)
+			count = count + 8
+		end
+
+		if bit_lshift(num, 60) == NUM_ZERO then
+			num = bit_rshift(num, 4)
+			count = count + 4
+		end
+
+		if bit_lshift(num, 62) == NUM_ZERO then
+			num = bit_rshift(num, 2)
+			count = count + 2
+		end
+
+		if bit_lshift(num, 63) == NUM_ZERO then
+			count = count + NUM_ONE
+		end
+
+		return count
+	end
+
+	function popcnt.i64(num)
+		local count = NUM_ZERO
+
+		while num ~= NUM_ZERO do
+			num = bit_and(num, num - NUM_ONE)
+			count = count + NUM_ONE
+		end
+
+		return count
+	end
+
+	module.clz = clz
+	module.ctz = ctz
+	module.popcnt = popcnt
+end
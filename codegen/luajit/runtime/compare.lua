@@ -0,0 +1,43 @@
+do
+	local le = {}
+	local lt = {}
+	local ge = {}
+	local gt = {}
+
+	function le.u32(lhs, rhs)
+		return u32(lhs) <= u32(rhs)
+	end
+
+	function lt.u32(lhs, rhs)
+		return u32(lhs) < u32(rhs)
+	end
+
+	function ge.u32(lhs, rhs)
+		return u32(lhs) >= u32(rhs)
+	end
+
+	function gt.u32(lhs, rhs)
+		return u32(lhs) > u32(rhs)
+	end
+
+	function le.u64(lhs, rhs)
+		return u64(lhs) <= u64(rhs)
+	end
+
+	function lt.u64(lhs, rhs)
+		return u64(lhs) < u64(rhs)
+	end
+
+	function ge.u64(lhs, rhs)
+		return u64(lhs) >= u64(rhs)
+	end
+
+	function gt.u64(lhs, rhs)
+		return u64(lhs) > u64(rhs)
+	end
+
+	module.le = le
+	module.lt = lt
+	module.ge = ge
+	module.gt = gt
+end
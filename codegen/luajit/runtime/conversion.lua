@@ -0,0 +1,215 @@
+do
+	local wrap = {}
+	local truncate = {}
+	local saturate = {}
+	local extend = {}
+	local convert = {}
+	local promote = {}
+	local demote = {}
+	local reinterpret = {}
+
+	local bit_and = bit.band
+
+	local NUM_MIN_I64 = bit.lshift(NUM_ONE, 63)
+	local NUM_MAX_I64 = bit.bnot(NUM_MIN_I64)
+	local NUM_MAX_U64 = bit.bnot(NUM_ZERO)
+
+	-- This would surely be an issue in a multi-thread environment...
+	-- ... thankfully this isn't one.
+	local RE_INSTANCE = ffi.new([[union {
+		int32_t i32;
+		int64_t i64;
+		float f32;
+		double f64;
+	}]])
+
+	function wrap.i32_i64(num)
+		RE_INSTANCE.i64 = num
+
+		return RE_INSTANCE.i32
+	end
+
+	truncate.i32_f32 = truncate_f64
+	truncate.i32_f64 = truncate_f64
+
+	function truncate.u32_f32(num)
+		return (to_signed(truncate_f64(num)))
+	end
+
+	truncate.u32_f64 = truncate.u32_f32
+
+	truncate.i64_f32 = i64
+	truncate.i64_f64 = i64
+	truncate.u64_f32 = i64
+
+	function truncate.u64_f64(num)
+		return (i64(u64(num)))
+	end
+
+	truncate.f32 = truncate_f64
+	truncate.f64 = truncate_f64
+
+	function saturate.i32_f32(num)
+		if num <= -0x80000000 then
+			return -0x80000000
+		elseif num >= 0x7FFFFFFF then
+			return 0x7FFFFFFF
+		else
+			return to_signed(truncate_f64(num))
+		end
+	end
+
+	saturate.i32_f64 = saturate.i32_f32
+
+	function saturate.u32_f32(num)
+		if num <= 0 then
+			return 0
+		elseif num >= 0xFFFFFFFF then
+			return -1
+		else
+			return to_signed(truncate_f64(num))
+		end
+	end
+
+	saturate.u32_f64 = saturate.u32_f32
+
+	function saturate.i64_f32(num)
+		if num >= 2 ^ 63 - 1 then
+			return NUM_MAX_I64
+		elseif num <= -2 ^ 63 then
+			return NUM_MIN_I64
+		elseif num ~= num then
+			return NUM_ZERO
+		else
+			return i64(num)
+		end
+	end
+
+	saturate.i64_f64 = saturate.i64_f32
+
+	function saturate.u64_f32(num)
+		if num >= 2 ^ 64 then
+			return NUM_MAX_U64
+		elseif num <= 0 or num ~= num then
+			return NUM_ZERO
+		else
+			return i64(u64(num))
+		end
+	end
+
+	saturate.u64_f64 = saturate.u64_f32
+
+	function extend.i32_n8(num)
+		num = bit_and(num, 0xFF)
+
+		if num >= 0x80 then
+			return num - 0x100
+		else
+			return num
+		end
+	end
+
+	function extend.i32_n16(num)
+		num = bit_and(num, 0xFFFF)
+
+		if num >= 0x8000 then
+			return num - 0x10000
+		else
+			return num
+		end
+	end
+
+	function extend.i64_n8(num)
+		num = bit_and(num, 0xFF * NUM_ONE)
+
+		if num >= 0x80 then
+			return num - 0x100
+		else
+			return num
+		end
+	end
+
+	function extend.i64_n16(num)
+		num = bit_and(num, 0xFFFF * NUM_ONE)
+
+		if num >= 0x8000 then
+			return num - 0x10000
+		else
+			return num
+		end
+	end
+
+	function extend.i64_n32(num)
+		num = bit_and(num, 0xFFFFFFFF * NUM_ONE)
+
+		if num >= 0x80000000 then
+			return num - 0x100000000
+		else
+			return num
+		end
+	end
+
+	extend.i64_i32 = i64
+
+	function extend.i64_u32(num)
+		RE_INSTANCE.i64 = NUM_ZERO
+		RE_INSTANCE.i32 = num
+
+		return RE_INSTANCE.i64
+	end
+
+	function convert.f32_i32(num)
+		return num
+	end
+
+	function convert.f32_u32(num)
+		return (to_number(u32(num)))
+	end
+
+	function convert.f32_u64(num)
+		return (to_number(u64(num)))
+	end
+
+	convert.f64_i32 = convert.f32_i32
+	convert.f64_u32 = convert.f32_u32
+	convert.f64_u64 = convert.f32_u64
+
+	function demote.f32_f64(num)
+		return num
+	end
+
+	promote.f64_f32 = demote.f32_f64
+
+	function reinterpret.i32_f32(num)
+		RE_INSTANCE.f32 = num
+
+		return RE_INSTANCE.i32
+	end
+
+	function reinterpret.i64_f64(num)
+		RE_INSTANCE.f64 = num
+
+		return RE_INSTANCE.i64
+	end
+
+	function reinterpret.f32_i32(num)
+		RE_INSTANCE.i32 = num
+
+		return RE_INSTANCE.f32
+	end
+
+	function reinterpret.f64_i64(num)
+		RE_INSTANCE.i64 = num
+
+		return RE_INSTANCE.f64
+	end
+
+	module.wrap = wrap
+	module.truncate = truncate
+	module.saturate = saturate
+	module.extend = extend
+	module.convert = convert
+	module.demote = demote
+	module.promote = promote
+	module.reinterpret = reinterpret
+end
@@ -0,0 +1,345 @@
+do
+	local load = {}
+	local store = {}
+	local allocator = {}
+
+	local trap_raise = module.trap.raise
+
+	ffi.cdef([[
+	union Any {
+		int8_t i8;
+		int16_t i16;
+		int32_t i32;
+		int64_t i64;
+
+		uint8_t u8;
+		uint16_t u16;
+		uint32_t u32;
+		uint64_t u64;
+
+		float f32;
+		double f64;
+	};
+
+	struct Memory {
+		uint32_t min;
+		uint32_t max;
+		union Any *data;
+	};
+
+	void *calloc(size_t num, size_t size);
+	void *realloc(void *ptr, size_t size);
+	void free(void *ptr);
+	]])
+
+	local alias_t = ffi.typeof("uint8_t *")
+	local any_t = ffi.typeof("union Any *")
+	local cast = ffi.cast
+
+	local WASM_PAGE_SIZE = 65536
+
+	local function by_offset(pointer, offset)
+		local aliased = cast(alias_t, pointer)
+
+		return cast(any_t, aliased + offset)
+	end
+
+	-- Accesses reaching past the current `min` pages trap instead of touching
+	-- whatever lies beyond the allocation.
+	local function by_checked(memory, addr, size)
+		if addr < 0 or addr + size > memory.min * WASM_PAGE_SIZE then
+			trap_raise("out of bounds memory access", size .. " bytes at " .. tostring(addr))
+		end
+
+		return by_offset(memory.data, addr)
+	end
+
+	function load.i32_i8(memory, addr)
+		return by_checked(memory, addr, 1).i8
+	end
+
+	function load.i32_u8(memory, addr)
+		return by_checked(memory, addr, 1).u8
+	end
+
+	function load.i32_i16(memory, addr)
+		return by_checked(memory, addr, 2).i16
+	end
+
+	function load.i32_u16(memory, addr)
+		return by_checked(memory, addr, 2).u16
+	end
+
+	function load.i32(memory, addr)
+		return by_checked(memory, addr, 4).i32
+	end
+
+	function load.i64_i8(memory, addr)
+		return (i64(by_checked(memory, addr, 1).i8))
+	end
+
+	function load.i64_u8(memory, addr)
+		return (i64(by_checked(memory, addr, 1).u8))
+	end
+
+	function load.i64_i16(memory, addr)
+		return (i64(by_checked(memory, addr, 2).i16))
+	end
+
+	function load.i64_u16(memory, addr)
+		return (i64(by_checked(memory, addr, 2).u16))
+	end
+
+	function load.i64_i32(memory, addr)
+		return (i64(by_checked(memory, addr, 4).i32))
+	end
+
+	function load.i64_u32(memory, addr)
+		return (i64(by_checked(memory, addr, 4).u32))
+	end
+
+	function load.i64(memory, addr)
+		return by_checked(memory, addr, 8).i64
+	end
+
+	function load.f32(memory, addr)
+		return by_checked(memory, addr, 4).f32
+	end
+
+	function load.f64(memory, addr)
+		return by_checked(memory, addr, 8).f64
+	end
+
+	function load.string(memory, addr, len)
+		local start = cast(alias_t, by_checked(memory, addr, len))
+
+		return ffi.string(start, len)
+	end
+
+	function store.i32_n8(memory, addr, value)
+		by_checked(memory, addr, 1).i8 = value
+	end
+
+	function store.i32_n16(memory, addr, value)
+		by_checked(memory, addr, 2).i16 = value
+	end
+
+	function store.i32(memory, addr, value)
+		by_checked(memory, addr, 4).i32 = value
+	end
+
+	function store.i64_n8(memory, addr, value)
+		by_checked(memory, addr, 1).i8 = value
+	end
+
+	function store.i64_n16(memory, addr, value)
+		by_checked(memory, addr, 2).i16 = value
+	end
+
+	function store.i64_n32(memory, addr, value)
+		by_checked(memory, addr, 4).i32 = value
+	end
+
+	function store.i64(memory, addr, value)
+		by_checked(memory, addr, 8).i64 = value
+	end
+
+	function store.f32(memory, addr, value)
+		by_checked(memory, addr, 4).f32 = value
+	end
+
+	function store.f64(memory, addr, value)
+		by_checked(memory, addr, 8).f64 = value
+	end
+
+	function store.string(memory, addr, data, len)
+		len = len or #data
+
+		local start = by_checked(memory, addr, len)
+
+		ffi.copy(start, data, len)
+	end
+
+	function store.init(memory, addr, data, offset, len)
+		local content = data or ""
+
+		if offset + len > #content then
+			trap_raise("out of bounds memory access", "data segment")
+		end
+
+		local start = by_checked(memory, addr, len)
+
+		ffi.copy(start, string.sub(content, offset + 1, offset + len), len)
+	end
+
+	function store.copy(memory_1, addr_1, memory_2, addr_2, len)
+		local start_1 = by_checked(memory_1, addr_1, len)
+		local start_2 = by_checked(memory_2, addr_2, len)
+
+		ffi.copy(start_1, start_2, len)
+	end
+
+	function store.fill(memory, addr, len, value)
+		local start = by_checked(memory, addr, len)
+
+		ffi.fill(start, len, value)
+	end
+
+	-- Memory is little-endian, so on big-endian hosts every multi-byte value
+	-- passes through a scratch union with its bytes reversed.
+	if ffi.abi("be") then
+		local scratch = ffi.new("union Any[1]")
+		local bytes = cast(alias_t, scratch)
+
+		local function load_swapped(memory, addr, size)
+			local source = cast(alias_t, by_checked(memory, addr, size))
+
+			for i = 0, size - 1 do
+				bytes[i] = source[size - 1 - i]
+			end
+
+			return scratch[0]
+		end
+
+		local function store_swapped(memory, addr, size)
+			local target = cast(alias_t, by_checked(memory, addr, size))
+
+			for i = 0, size - 1 do
+				target[i] = bytes[size - 1 - i]
+			end
+		end
+
+		function load.i32_i16(memory, addr)
+			return load_swapped(memory, addr, 2).i16
+		end
+
+		function load.i32_u16(memory, addr)
+			return load_swapped(memory, addr, 2).u16
+		end
+
+		function load.i32(memory, addr)
+			return load_swapped(memory, addr, 4).i32
+		end
+
+		function load.i64_i16(memory, addr)
+			return (i64(load_swapped(memory, addr, 2).i16))
+		end
+
+		function load.i64_u16(memory, addr)
+			return (i64(load_swapped(memory, addr, 2).u16))
+		end
+
+		function load.i64_i32(memory, addr)
+			return (i64(load_swapped(memory, addr, 4).i32))
+		end
+
+		function load.i64_u32(memory, addr)
+			return (i64(load_swapped(memory, addr, 4).u32))
+		end
+
+		function load.i64(memory, addr)
+			return load_swapped(memory, addr, 8).i64
+		end
+
+		function load.f32(memory, addr)
+			return load_swapped(memory, addr, 4).f32
+		end
+
+		function load.f64(memory, addr)
+			return load_swapped(memory, addr, 8).f64
+		end
+
+		function store.i32_n16(memory, addr, value)
+			scratch[0].i16 = value
+			store_swapped(memory, addr, 2)
+		end
+
+		function store.i32(memory, addr, value)
+			scratch[0].i32 = value
+			store_swapped(memory, addr, 4)
+		end
+
+		function store.i64_n16(memory, addr, value)
+			scratch[0].i16 = value
+			store_swapped(memory, addr, 2)
+		end
+
+		function store.i64_n32(memory, addr, value)
+			scratch[0].i32 = value
+			store_swapped(memory, addr, 4)
+		end
+
+		function store.i64(memory, addr, value)
+			scratch[0].i64 = value
+			store_swapped(memory, addr, 8)
+		end
+
+		function store.f32(memory, addr, value)
+			scratch[0].f32 = value
+			store_swapped(memory, addr, 4)
+		end
+
+		function store.f64(memory, addr, value)
+			scratch[0].f64 = value
+			store_swapped(memory, addr, 8)
+		end
+	end
+
+	local function finalizer(memory)
+		ffi.C.free(memory.data)
+	end
+
+	-- A failed `realloc` leaves the old block in place, so the memory is untouched.
+	local function grow_unchecked(memory, old, new)
+		local data = ffi.C.realloc(memory.data, new)
+
+		if data == nil then
+			return false
+		end
+
+		memory.data = data
+
+		ffi.fill(by_offset(memory.data, old), new - old, 0)
+
+		return true
+	end
+
+	function allocator.new(min, max)
+		local data = ffi.C.calloc(min, WASM_PAGE_SIZE)
+
+		assert(data ~= nil, "failed to allocate")
+
+		local memory = ffi.new("struct Memory", min, max, data)
+
+		return ffi.gc(memory, finalizer)
+	end
+
+	function allocator.grow(memory, num)
+		if num == 0 then
+			return memory.min
+		end
+
+		local old = memory.min
+		local new = old + num
+
+		-- Page counts are unsigned, so a negative `num` asks for more than any maximum.
+		if num < 0 or new > memory.max then
+			return -1
+		elseif not grow_unchecked(memory, old * WASM_PAGE_SIZE, new * WASM_PAGE_SIZE) then
+			return -1
+		end
+
+		memory.min = new
+
+		return old
+	end
+
+	-- `min` counts pages, so this is the length in bytes the accesses check against.
+	function allocator.byte_size(memory)
+		return memory.min * WASM_PAGE_SIZE
+	end
+
+	module.load = load
+	module.store = store
+	module.allocator = allocator
+end
@@ -0,0 +1,196 @@
+do
+	local add = {}
+	local sub = {}
+	local mul = {}
+	local div = {}
+	local rem = {}
+	local neg = {}
+	local min = {}
+	local max = {}
+	local copysign = {}
+	local nearest = {}
+
+	local trap_raise = module.trap.raise
+	local math_abs = math.abs
+	local math_min = math.min
+	local math_max = math.max
+	local math_fmod = math.fmod
+
+	local NUM_MIN_I64 = bit.lshift(NUM_ONE, 63)
+
+	local RE_INSTANCE = ffi.new([[union {
+		double f64;
+		struct { int32_t a32, b32; };
+	}]])
+
+	local function round(num)
+		if num >= 0 then
+			return (math_floor(num + 0.5))
+		else
+			return (math_ceil(num - 0.5))
+		end
+	end
+
+	function add.i32(lhs, rhs)
+		return (to_signed(lhs + rhs))
+	end
+
+	function sub.i32(lhs, rhs)
+		return (to_signed(lhs - rhs))
+	end
+
+	function add.i64(lhs, rhs)
+		return lhs + rhs
+	end
+
+	function sub.i64(lhs, rhs)
+		return lhs - rhs
+	end
+
+	function mul.i32(lhs, rhs)
+		return (to_signed(NUM_ONE * lhs * rhs))
+	end
+
+	function div.i32(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		elseif lhs == -0x80000000 and rhs == -1 then
+			trap_raise("integer overflow", "quotient of -2147483648 by -1")
+		end
+
+		return (truncate_f64(lhs / rhs))
+	end
+
+	function rem.i32(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		end
+
+		return (math_fmod(lhs, rhs))
+	end
+
+	function div.u32(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		end
+
+		lhs = to_number(u32(lhs))
+		rhs = to_number(u32(rhs))
+
+		return (to_signed(math_floor(lhs / rhs)))
+	end
+
+	function rem.u32(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		end
+
+		lhs = to_number(u32(lhs))
+		rhs = to_number(u32(rhs))
+
+		return (to_signed(lhs % rhs))
+	end
+
+	-- Plain `/` and `%` on `int64_t` give made up results instead of trapping, so
+	-- the cases Wasm traps on are checked first.
+	function div.i64(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		elseif lhs == NUM_MIN_I64 and rhs == -1 then
+			trap_raise("integer overflow", "quotient of -9223372036854775808 by -1")
+		end
+
+		return lhs / rhs
+	end
+
+	function rem.i64(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		elseif rhs == -1 then
+			return NUM_ZERO
+		end
+
+		return lhs % rhs
+	end
+
+	function div.u64(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		end
+
+		return (i64(u64(lhs) / u64(rhs)))
+	end
+
+	function rem.u64(lhs, rhs)
+		if rhs == 0 then
+			trap_raise("integer divide by zero", "division by zero")
+		end
+
+		return (i64(u64(lhs) % u64(rhs)))
+	end
+
+	function neg.f32(num)
+		return -num
+	end
+
+	function min.f32(lhs, rhs)
+		if lhs ~= lhs then
+			return lhs
+		elseif rhs ~= rhs then
+			return rhs
+		else
+			return (math_min(lhs, rhs))
+		end
+	end
+
+	function max.f32(lhs, rhs)
+		if lhs ~= lhs then
+			return lhs
+		elseif rhs ~= rhs then
+			return rhs
+		else
+			return (math_max(lhs, rhs))
+		end
+	end
+
+	function copysign.f32(lhs, rhs)
+		RE_INSTANCE.f64 = rhs
+
+		if RE_INSTANCE.b32 >= 0 then
+			return (math_abs(lhs))
+		else
+			return -math_abs(lhs)
+		end
+	end
+
+	function nearest.f32(num)
+		local result = round(num)
+
+		if (math_abs(num) + 0.5) % 2 == 1 then
+			if result >= 0 then
+				result = result - 1
+			else
+				result = result + 1
+			end
+		end
+
+		return result
+	end
+
+	neg.f64 = neg.f32
+	min.f64 = min.f32
+	max.f64 = max.f32
+	copysign.f64 = copysign.f32
+	nearest.f64 = nearest.f32
+
+	module.add = add
+	module.sub = sub
+	module.mul = mul
+	module.div = div
+	module.rem = rem
+	module.min = min
+	module.max = max
+	module.neg = neg
+	module.copysign = copysign
+	module.nearest = nearest
+end
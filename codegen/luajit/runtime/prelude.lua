@@ -0,0 +1,65 @@
+local module = {}
+
+local bit = require("bit")
+local ffi = require("ffi")
+
+local u32 = ffi.typeof("uint32_t")
+local u64 = ffi.typeof("uint64_t")
+local i64 = ffi.typeof("int64_t")
+
+local math_ceil = math.ceil
+local math_floor = math.floor
+local to_number = tonumber
+local to_signed = bit.tobit
+
+local NUM_ZERO = i64(0)
+local NUM_ONE = i64(1)
+
+do
+	local trap = {}
+
+	local handler = nil
+
+	-- Every trap is raised as a `{ kind, detail }` table, so the host can tell it
+	-- apart from other errors with `trap.kind`. The `kind` is always one of
+	-- "unreachable", "integer divide by zero", "integer overflow",
+	-- "out of bounds memory access", or "out of bounds table access", while the
+	-- `detail` is meant for people.
+	local meta = {}
+
+	function meta.__tostring(value)
+		return value.kind .. ": " .. value.detail
+	end
+
+	-- Traps raised by the generated code are first shown to the handler, if any,
+	-- which may throw its own error in place of the default one.
+	function trap.raise(kind, detail)
+		if handler then
+			handler(kind, detail)
+		end
+
+		error(setmetatable({ kind = kind, detail = detail }, meta), 2)
+	end
+
+	function trap.kind(value)
+		if getmetatable(value) == meta then
+			return value.kind
+		end
+
+		return nil
+	end
+
+	function trap.set_handler(value)
+		handler = value
+	end
+
+	module.trap = trap
+end
+
+local function truncate_f64(num)
+	if num >= 0 then
+		return (math_floor(num))
+	else
+		return (math_ceil(num))
+	end
+end
@@ -0,0 +1,42 @@
+do
+	local table_ops = {}
+
+	local trap_raise = module.trap.raise
+
+	local function check(target, index)
+		if index < 0 or index >= target.min then
+			trap_raise("out of bounds table access", "index " .. index .. " is past size " .. target.min)
+		end
+	end
+
+	function table_ops.get(target, index)
+		check(target, index)
+
+		return target.data[index]
+	end
+
+	function table_ops.set(target, index, value)
+		check(target, index)
+
+		target.data[index] = value
+	end
+
+	function table_ops.grow(target, value, num)
+		local old = target.min
+		local new = old + num
+
+		if new > target.max then
+			return -1
+		end
+
+		for i = old, new - 1 do
+			target.data[i] = value
+		end
+
+		target.min = new
+
+		return old
+	end
+
+	module.table = table_ops
+end
@@ -50,6 +50,8 @@ pub trait IntoNameTuple {
 impl IntoNameTuple for UnOpType {
 	fn into_name_tuple(self) -> (&'static str, &'static str) {
 		match self {
+			Self::Eqz_I32 => ("eqz", "i32"),
+			Self::Eqz_I64 => ("eqz", "i64"),
 			Self::Clz_I32 => ("clz", "i32"),
 			Self::Ctz_I32 => ("ctz", "i32"),
 			Self::Popcnt_I32 => ("popcnt", "i32"),
@@ -207,14 +209,24 @@ pub trait TryIntoSymbol {
 	fn try_into_symbol(self) -> Option<&'static str>;
 }
 
+impl TryIntoSymbol for UnOpType {
+	fn try_into_symbol(self) -> Option<&'static str> {
+		let result = match self {
+			Self::Eqz_I32 | Self::Eqz_I64 => "==",
+			_ => return None,
+		};
+
+		Some(result)
+	}
+}
+
 impl TryIntoSymbol for BinOpType {
 	fn try_into_symbol(self) -> Option<&'static str> {
 		let result = match self {
 			Self::Add_I64 | Self::Add_F32 | Self::Add_F64 => "+",
 			Self::Sub_I64 | Self::Sub_F32 | Self::Sub_F64 => "-",
 			Self::Mul_I64 | Self::Mul_F32 | Self::Mul_F64 => "*",
-			Self::DivS_I64 | Self::Div_F32 | Self::Div_F64 => "/",
-			Self::RemS_I64 => "%",
+			Self::Div_F32 | Self::Div_F64 => "/",
 			_ => return None,
 		};
 
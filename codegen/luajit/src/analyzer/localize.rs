@@ -2,8 +2,8 @@ use std::collections::BTreeSet;
 
 use wasm_ast::{
 	node::{
-		BinOp, CmpOp, FuncData, LoadAt, MemoryCopy, MemoryFill, MemoryGrow, MemorySize, StoreAt,
-		UnOp,
+		AtomicCmpxchg, AtomicRmw, BinOp, CmpOp, FuncData, LoadAt, MemoryCopy, MemoryFill,
+		MemoryGrow, MemoryInit, MemorySize, StoreAt, UnOp,
 	},
 	visit::{Driver, Visitor},
 };
@@ -31,6 +31,10 @@ impl Visitor for Visit {
 	}
 
 	fn visit_un_op(&mut self, v: &UnOp) {
+		if v.op_type().try_into_symbol().is_some() {
+			return;
+		}
+
 		let name = v.op_type().into_name_tuple();
 
 		self.local_set.insert(name);
@@ -56,6 +60,22 @@ impl Visitor for Visit {
 		self.local_set.insert(name);
 	}
 
+	fn visit_atomic_rmw(&mut self, v: &AtomicRmw) {
+		self.memory_set.insert(v.memory());
+		self.local_set.insert(("load", v.load_type().into_name()));
+		self.local_set.insert(("store", v.store_type().into_name()));
+
+		if let Some(op_type) = v.op_type() {
+			self.local_set.insert(op_type.into_name_tuple());
+		}
+	}
+
+	fn visit_atomic_cmpxchg(&mut self, v: &AtomicCmpxchg) {
+		self.memory_set.insert(v.memory());
+		self.local_set.insert(("load", v.load_type().into_name()));
+		self.local_set.insert(("store", v.store_type().into_name()));
+	}
+
 	fn visit_memory_size(&mut self, m: &MemorySize) {
 		self.memory_set.insert(m.memory());
 	}
@@ -72,6 +92,10 @@ impl Visitor for Visit {
 	fn visit_memory_fill(&mut self, m: &MemoryFill) {
 		self.memory_set.insert(m.destination().memory());
 	}
+
+	fn visit_memory_init(&mut self, m: &MemoryInit) {
+		self.memory_set.insert(m.destination().memory());
+	}
 }
 
 pub fn visit(ast: &FuncData) -> (BTreeSet<(&'static str, &'static str)>, BTreeSet<usize>) {
@@ -4,35 +4,56 @@ use std::{
 };
 
 use wasm_ast::node::{
-	BinOp, CmpOp, Expression, GetGlobal, LoadAt, Local, MemorySize, Select, Temporary, UnOp, Value,
+	BinOp, CmpOp, Expression, GetGlobal, LoadAt, Local, MemorySize, RefFunc, Select, TableGet,
+	TableSize, Temporary, UnOp, UnOpType, Value,
 };
 
 use crate::analyzer::into_string::{IntoName, IntoNameTuple, TryIntoSymbol};
 
 use super::manager::{write_separated, Driver, Manager};
 
-macro_rules! impl_write_number {
-	($name:tt, $numeric:ty) => {
-		fn $name(number: $numeric, w: &mut dyn Write) -> Result<()> {
-			match (number.classify(), number.is_sign_negative()) {
-				(FpCategory::Nan, true) => write!(w, "(0.0 / 0.0)"),
-				(FpCategory::Nan, false) => write!(w, "-(0.0 / 0.0)"),
-				(FpCategory::Infinite, true) => write!(w, "-math.huge"),
-				(FpCategory::Infinite, false) => write!(w, "math.huge"),
-				_ => write!(w, "{number:e}"),
-			}
-		}
-	};
+fn write_f64(number: f64, w: &mut dyn Write) -> Result<()> {
+	match (number.classify(), number.is_sign_negative()) {
+		(FpCategory::Nan, true) => write!(w, "(0.0 / 0.0)"),
+		(FpCategory::Nan, false) => write!(w, "-(0.0 / 0.0)"),
+		(FpCategory::Infinite, true) => write!(w, "-math.huge"),
+		(FpCategory::Infinite, false) => write!(w, "math.huge"),
+		_ => write!(w, "{number:e}"),
+	}
 }
 
 impl Driver for Select {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		// References may be `nil`, which `and` and `or` would skip over.
+		if self.is_reference() {
+			write!(w, "(function() if ")?;
+			Condition(self.condition()).write(mng, w)?;
+			write!(w, " then return ")?;
+			self.on_true().write(mng, w)?;
+			write!(w, " end return ")?;
+			self.on_false().write(mng, w)?;
+			return write!(w, " end)()");
+		}
+
+		let mut last = self;
+
 		write!(w, "(")?;
-		Condition(self.condition()).write(mng, w)?;
-		write!(w, " and ")?;
-		self.on_true().write(mng, w)?;
-		write!(w, " or ")?;
-		self.on_false().write(mng, w)?;
+
+		// Numbers are never falsy, so chains of `select` need no inner parentheses.
+		loop {
+			Condition(last.condition()).write(mng, w)?;
+			write!(w, " and ")?;
+			last.on_true().write(mng, w)?;
+			write!(w, " or ")?;
+
+			let Expression::Select(next) = last.on_false() else {
+				break;
+			};
+
+			last = next;
+		}
+
+		last.on_false().write(mng, w)?;
 		write!(w, ")")
 	}
 }
@@ -67,6 +88,12 @@ impl Driver for GetGlobal {
 	}
 }
 
+impl Driver for RefFunc {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "FUNC_LIST[{}]", self.function())
+	}
+}
+
 impl Driver for LoadAt {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		let name = self.load_type().into_name();
@@ -89,22 +116,55 @@ impl Driver for MemorySize {
 	}
 }
 
-impl_write_number!(write_f32, f32);
-impl_write_number!(write_f64, f64);
+impl Driver for TableGet {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.table.get(TABLE_LIST[{}], ", self.table())?;
+		self.index().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableSize {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "TABLE_LIST[{}].min", self.table())
+	}
+}
 
 impl Driver for Value {
 	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		match self {
 			Self::I32(i) => write!(w, "{i}"),
 			Self::I64(i) => write!(w, "{i}LL"),
-			Self::F32(f) => write_f32(*f, w),
+			// Every `f32` is exactly representable as an `f64`, so widening loses
+			// nothing and the shortest `f64` form reparses to the same `f32`.
+			Self::F32(f) => write_f64(f64::from(*f), w),
 			Self::F64(f) => write_f64(*f, w),
 		}
 	}
 }
 
+struct UnOpBoolean<'a>(&'a UnOp, &'static str);
+
+impl Driver for UnOpBoolean<'_> {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let zero = match self.0.op_type() {
+			UnOpType::Eqz_I64 => "0LL",
+			_ => "0",
+		};
+
+		self.0.rhs().write(mng, w)?;
+		write!(w, " {} {zero}", self.1)
+	}
+}
+
 impl Driver for UnOp {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if let Some(symbol) = self.op_type().try_into_symbol() {
+			write!(w, "(")?;
+			UnOpBoolean(self, symbol).write(mng, w)?;
+			return write!(w, " and 1 or 0)");
+		}
+
 		let (a, b) = self.op_type().into_name_tuple();
 
 		write!(w, "{a}_{b}(")?;
@@ -162,12 +222,22 @@ impl Driver for CmpOp {
 	}
 }
 
+fn unary_symbol(data: &Expression) -> Option<(&UnOp, &'static str)> {
+	let Expression::UnOp(node) = data else {
+		return None;
+	};
+
+	node.op_type().try_into_symbol().map(|v| (node, v))
+}
+
 pub struct Condition<'a>(pub &'a Expression);
 
 impl Driver for Condition<'_> {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		if let Expression::CmpOp(node) = self.0 {
 			CmpOpBoolean(node).write(mng, w)
+		} else if let Some(symbol) = unary_symbol(self.0) {
+			UnOpBoolean(symbol.0, symbol.1).write(mng, w)
 		} else {
 			self.0.write(mng, w)?;
 			write!(w, " ~= 0")
@@ -182,8 +252,12 @@ impl Driver for Expression {
 			Self::GetTemporary(e) => e.write(mng, w),
 			Self::GetLocal(e) => e.write(mng, w),
 			Self::GetGlobal(e) => e.write(mng, w),
+			Self::RefFunc(e) => e.write(mng, w),
+			Self::RefNull => write!(w, "nil"),
 			Self::LoadAt(e) => e.write(mng, w),
 			Self::MemorySize(e) => e.write(mng, w),
+			Self::TableGet(e) => e.write(mng, w),
+			Self::TableSize(e) => e.write(mng, w),
 			Self::Value(e) => e.write(mng, w),
 			Self::UnOp(e) => e.write(mng, w),
 			Self::BinOp(e) => e.write(mng, w),
@@ -51,6 +51,7 @@ fn get_pinned_registers(
 }
 
 pub struct Manager {
+	function_index: Option<usize>,
 	table_map: HashMap<usize, usize>,
 	num_local: usize,
 	num_temp: usize,
@@ -62,6 +63,7 @@ pub struct Manager {
 impl Manager {
 	pub fn empty() -> Self {
 		Self {
+			function_index: None,
 			table_map: HashMap::new(),
 			num_local: 0,
 			num_temp: usize::MAX,
@@ -82,6 +84,7 @@ impl Manager {
 		);
 
 		Self {
+			function_index: None,
 			table_map,
 			num_local,
 			num_temp,
@@ -91,6 +94,14 @@ impl Manager {
 		}
 	}
 
+	pub fn set_function_index(&mut self, index: usize) {
+		self.function_index = Some(index);
+	}
+
+	pub const fn function_index(&self) -> Option<usize> {
+		self.function_index
+	}
+
 	pub fn get_table_index(&self, table: &BrTable) -> usize {
 		let id = std::ptr::from_ref(table) as usize;
 
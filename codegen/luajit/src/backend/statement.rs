@@ -4,13 +4,17 @@ use std::{
 };
 
 use wasm_ast::node::{
-	Block, Br, BrIf, BrTable, Call, CallIndirect, FuncData, If, LabelType, MemoryCopy, MemoryFill,
-	MemoryGrow, ResultList, SetGlobal, SetLocal, SetTemporary, Statement, StoreAt, Terminator,
+	AtomicCmpxchg, AtomicRmw, Block, Br, BrIf, BrTable, Call, CallIndirect, DataDrop, Expression,
+	FuncData, If, LabelType, LoadType, MemoryCopy, MemoryFill, MemoryGrow, MemoryInit, ResultList,
+	SetGlobal, SetLocal, SetTemporary, Statement, StoreAt, StoreType, TableGrow, TableSet,
+	Terminator,
 };
 use wasmparser::ValType;
 
 use crate::{
-	analyzer::into_string::IntoName, backend::manager::write_separated, indentation, indented, line,
+	analyzer::into_string::{IntoName, IntoNameTuple},
+	backend::manager::write_separated,
+	indentation, indented, line,
 };
 
 use super::{
@@ -132,12 +136,36 @@ impl Driver for BrTable {
 	}
 }
 
+fn write_unreachable(mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	indented!(
+		mng,
+		w,
+		r#"rt.trap.raise("unreachable", "out of code bounds"#
+	)?;
+
+	if let Some(index) = mng.function_index() {
+		write!(w, " in function {index}")?;
+	}
+
+	writeln!(w, r#"")"#)
+}
+
 impl Driver for Terminator {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		match self {
-			Self::Unreachable => line!(mng, w, r#"error("out of code bounds")"#),
+			Self::Unreachable => write_unreachable(mng, w),
 			Self::Br(s) => s.write(mng, w),
 			Self::BrTable(s) => s.write(mng, w),
+			Self::ReturnCall(s) => {
+				indented!(mng, w, "do return ")?;
+				s.write(mng, w)?;
+				writeln!(w, " end")
+			}
+			Self::ReturnCallIndirect(s) => {
+				indented!(mng, w, "do return ")?;
+				s.write(mng, w)?;
+				writeln!(w, " end")
+			}
 		}
 	}
 }
@@ -326,6 +354,118 @@ impl Driver for MemoryFill {
 	}
 }
 
+impl Driver for MemoryInit {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.destination().memory();
+
+		write!(w, "rt.store.init(memory_at_{memory}, ")?;
+		self.destination().pointer().write(mng, w)?;
+		write!(w, ", DATA_LIST[{}], ", self.data())?;
+		self.offset().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for DataDrop {
+	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "DATA_LIST[{}] = nil", self.data())
+	}
+}
+
+impl Driver for TableSet {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write!(w, "rt.table.set(TABLE_LIST[{}], ", self.table())?;
+		self.index().write(mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableGrow {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		self.result().write(mng, w)?;
+		write!(w, " = rt.table.grow(TABLE_LIST[{}], ", self.table())?;
+		self.value().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+fn write_atomic_start(
+	name: &str,
+	memory: usize,
+	pointer: &Expression,
+	offset: u32,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	write!(w, "rt.atomic.{name}(memory_at_{memory}, ")?;
+	pointer.write(mng, w)?;
+
+	if offset != 0 {
+		write!(w, " + {offset}")?;
+	}
+
+	Ok(())
+}
+
+fn write_atomic_width(load_type: LoadType, store_type: StoreType, w: &mut dyn Write) -> Result<()> {
+	let load = load_type.into_name();
+	let store = store_type.into_name();
+
+	write!(w, ", load_{load}, store_{store}")
+}
+
+impl Driver for AtomicRmw {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let name = if self.op_type().is_some() {
+			"rmw"
+		} else {
+			"xchg"
+		};
+
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		write_atomic_start(name, self.memory(), self.pointer(), self.offset(), mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write_atomic_width(self.load_type(), self.store_type(), w)?;
+
+		if let Some(op_type) = self.op_type() {
+			let (head, tail) = op_type.into_name_tuple();
+
+			write!(w, ", {head}_{tail}")?;
+		}
+
+		write!(w, ")")
+	}
+}
+
+impl Driver for AtomicCmpxchg {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		write_atomic_start(
+			"cmpxchg",
+			self.memory(),
+			self.pointer(),
+			self.offset(),
+			mng,
+			w,
+		)?;
+		write!(w, ", ")?;
+		self.expected().write(mng, w)?;
+		write!(w, ", ")?;
+		self.replacement().write(mng, w)?;
+		write_atomic_width(self.load_type(), self.store_type(), w)?;
+		write!(w, ")")
+	}
+}
+
 fn write_stat(stat: &dyn Driver, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 	indentation!(mng, w)?;
 	stat.write(mng, w)?;
@@ -347,6 +487,12 @@ impl Driver for Statement {
 			Self::MemoryGrow(s) => write_stat(s, mng, w),
 			Self::MemoryCopy(s) => write_stat(s, mng, w),
 			Self::MemoryFill(s) => write_stat(s, mng, w),
+			Self::MemoryInit(s) => write_stat(s, mng, w),
+			Self::DataDrop(s) => write_stat(s, mng, w),
+			Self::TableSet(s) => write_stat(s, mng, w),
+			Self::TableGrow(s) => write_stat(s, mng, w),
+			Self::AtomicRmw(s) => write_stat(s, mng, w),
+			Self::AtomicCmpxchg(s) => write_stat(s, mng, w),
 		}
 	}
 }
@@ -1,7 +1,12 @@
-pub static RUNTIME: &str = include_str!("../runtime/runtime.lua");
-
-pub use translator::{from_inst_list, from_module_typed, from_module_untyped};
+pub use dependencies::Dependencies;
+pub use runtime::RUNTIME;
+pub use translator::{
+	from_inst_list, from_module_typed, from_module_untyped, function_dependencies,
+	write_runtime_trimmed,
+};
 
 mod analyzer;
 mod backend;
+mod dependencies;
+mod runtime;
 mod translator;
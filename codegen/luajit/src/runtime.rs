@@ -0,0 +1,181 @@
+use std::{
+	collections::BTreeSet,
+	io::{Result, Write},
+};
+
+pub static RUNTIME: &str = concat!(
+	include_str!("../runtime/prelude.lua"),
+	"\n",
+	include_str!("../runtime/numeric.lua"),
+	"\n",
+	include_str!("../runtime/bits.lua"),
+	"\n",
+	include_str!("../runtime/compare.lua"),
+	"\n",
+	include_str!("../runtime/conversion.lua"),
+	"\n",
+	include_str!("../runtime/memory.lua"),
+	"\n",
+	include_str!("../runtime/table.lua"),
+	"\n",
+	include_str!("../runtime/atomic.lua"),
+	"\nreturn module\n",
+);
+
+// Sections holding per-type operations are trimmed down to the definitions in
+// use, while the rest is always written whole.
+static SECTION_LIST: [(&str, bool); 8] = [
+	(include_str!("../runtime/prelude.lua"), false),
+	(include_str!("../runtime/numeric.lua"), true),
+	(include_str!("../runtime/bits.lua"), true),
+	(include_str!("../runtime/compare.lua"), true),
+	(include_str!("../runtime/conversion.lua"), true),
+	(include_str!("../runtime/memory.lua"), true),
+	(include_str!("../runtime/table.lua"), false),
+	(include_str!("../runtime/atomic.lua"), false),
+];
+
+// Called by the module setup code or by the host rather than by any function
+// body, so they never show up in the localize sets.
+static SETUP_LIST: [(&str, &str); 8] = [
+	("allocator", "new"),
+	("allocator", "grow"),
+	("allocator", "byte_size"),
+	("load", "string"),
+	("store", "string"),
+	("store", "init"),
+	("store", "copy"),
+	("store", "fill"),
+];
+
+type Name<'a> = (&'a str, &'a str);
+
+fn split_name(text: &str) -> Option<Name<'_>> {
+	let (head, tail) = text.split_once('.')?;
+	let is_name =
+		|v: &str| !v.is_empty() && v.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+
+	(is_name(head) && is_name(tail)).then_some((head, tail))
+}
+
+// Operation tables are the ones declared as `local name = {}` at the top of a
+// section, which keeps `module.*` and locals like `RE_INSTANCE` out of it.
+fn table_set(source: &str) -> BTreeSet<&str> {
+	source
+		.lines()
+		.filter_map(|line| line.strip_prefix("\tlocal ")?.strip_suffix(" = {}"))
+		.collect()
+}
+
+enum Line<'a> {
+	Function(Name<'a>),
+	Alias(Name<'a>, Option<Name<'a>>),
+	Other,
+}
+
+fn parse_line<'a>(line: &'a str, table_set: &BTreeSet<&str>) -> Line<'a> {
+	let text = line.trim_start_matches('\t');
+
+	if let Some(rest) = text.strip_prefix("function ") {
+		let name = rest.split_once('(').and_then(|v| split_name(v.0));
+
+		if let Some(name) = name.filter(|v| table_set.contains(v.0)) {
+			return Line::Function(name);
+		}
+	} else if let Some((lhs, rhs)) = text.split_once(" = ") {
+		if let Some(name) = split_name(lhs).filter(|v| table_set.contains(v.0)) {
+			return Line::Alias(name, split_name(rhs));
+		}
+	}
+
+	Line::Other
+}
+
+// Aliases such as `neg.f64 = neg.f32` need their source kept along with them.
+fn resolve_aliases<'a>(used: &BTreeSet<Name<'a>>) -> BTreeSet<Name<'a>> {
+	let mut result = used.clone();
+
+	loop {
+		let len = result.len();
+
+		for &(source, _) in SECTION_LIST.iter().filter(|v| v.1) {
+			let table_set = table_set(source);
+
+			for line in source.lines() {
+				if let Line::Alias(name, Some(target)) = parse_line(line, &table_set) {
+					if result.contains(&name) {
+						result.insert(target);
+					}
+				}
+			}
+		}
+
+		if result.len() == len {
+			return result;
+		}
+	}
+}
+
+fn write_trimmed(source: &str, used: &BTreeSet<Name>, w: &mut dyn Write) -> Result<()> {
+	let table_set = table_set(source);
+	let mut lines = source.lines();
+	let mut comment_list = Vec::new();
+	let mut last_blank = false;
+
+	while let Some(line) = lines.next() {
+		// Comments belong to the definition below them and go away with it.
+		if line.trim_start().starts_with("--") {
+			comment_list.push(line);
+
+			continue;
+		}
+
+		match parse_line(line, &table_set) {
+			Line::Function(name) if !used.contains(&name) => {
+				let indent = &line[..line.len() - line.trim_start_matches('\t').len()];
+				let end = format!("{indent}end");
+
+				lines.by_ref().find(|v| *v == end);
+				comment_list.clear();
+
+				continue;
+			}
+			Line::Alias(name, _) if !used.contains(&name) => {
+				comment_list.clear();
+
+				continue;
+			}
+			_ => {}
+		}
+
+		let is_blank = line.is_empty();
+
+		for comment in comment_list.drain(..) {
+			writeln!(w, "{comment}")?;
+		}
+
+		if !(is_blank && last_blank) {
+			writeln!(w, "{line}")?;
+		}
+
+		last_blank = is_blank;
+	}
+
+	Ok(())
+}
+
+pub fn write_used(used: &BTreeSet<(&'static str, &'static str)>, w: &mut dyn Write) -> Result<()> {
+	let used = resolve_aliases(&used.iter().copied().chain(SETUP_LIST).collect());
+
+	for (source, is_trimmed) in SECTION_LIST {
+		if is_trimmed {
+			write_trimmed(source, &used, w)?;
+		} else {
+			write!(w, "{source}")?;
+		}
+
+		writeln!(w)?;
+	}
+
+	writeln!(w, "return module")
+}
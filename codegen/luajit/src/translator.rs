@@ -1,6 +1,6 @@
 use std::{
 	collections::BTreeSet,
-	io::{Result, Write},
+	io::{Error, ErrorKind, Result, Write},
 };
 
 use wasm_ast::{
@@ -10,12 +10,14 @@ use wasm_ast::{
 };
 use wasmparser::{
 	ConstExpr, Data, DataKind, Element, ElementItems, ElementKind, Export, Import, Operator,
-	OperatorsReader,
+	OperatorsReader, TypeRef,
 };
 
 use crate::{
 	analyzer::localize,
 	backend::manager::{Driver, Manager},
+	dependencies::Dependencies,
+	runtime,
 };
 
 trait AsIEName {
@@ -41,7 +43,9 @@ fn reader_to_code(reader: OperatorsReader) -> Vec<Operator> {
 }
 
 fn write_named_array(name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
-	let Some(len) = len.checked_sub(1) else { return Ok(()) };
+	let Some(len) = len.checked_sub(1) else {
+		return Ok(());
+	};
 
 	writeln!(w, "local {name} = table_new({len}, 1)")
 }
@@ -57,17 +61,39 @@ fn write_constant(init: &ConstExpr, type_info: &TypeInfo, w: &mut dyn Write) ->
 	}
 }
 
+// Mutable globals must be shared as `{ value = ... }` tables for writes to be seen
+// on both sides, while immutable ones are boxed here when passed as numbers.
+fn write_global_import(target: &str, mutable: bool, w: &mut dyn Write) -> Result<()> {
+	write!(w, "\t")?;
+
+	if mutable {
+		writeln!(
+			w,
+			r#"assert(type({target}) == "table", "mutable global import must be a table")"#
+		)
+	} else {
+		writeln!(
+			w,
+			r#"if type({target}) ~= "table" then {target} = {{ value = {target} }} end"#
+		)
+	}
+}
+
 fn write_import_of(list: &[Import], wanted: External, w: &mut dyn Write) -> Result<()> {
 	let lower = wanted.as_ie_name();
 	let upper = lower.to_uppercase();
 
-	for (i, Import { name, module, .. }) in list
+	for (i, Import { name, module, ty }) in list
 		.iter()
 		.filter(|v| External::from(v.ty) == wanted)
 		.enumerate()
 	{
 		write!(w, "\t")?;
 		writeln!(w, r#"{upper}[{i}] = wasm["{module}"].{lower}["{name}"]"#)?;
+
+		if let TypeRef::Global(ty) = ty {
+			write_global_import(&format!("{upper}[{i}]"), ty.mutable, w)?;
+		}
 	}
 
 	Ok(())
@@ -77,9 +103,17 @@ fn write_export_of(list: &[Export], wanted: External, w: &mut dyn Write) -> Resu
 	let lower = wanted.as_ie_name();
 	let upper = lower.to_uppercase();
 
+	// Sorted by name so that the output does not depend on declaration order.
+	let mut list: Vec<_> = list
+		.iter()
+		.filter(|v| External::from(v.kind) == wanted)
+		.collect();
+
+	list.sort_by_key(|v| v.name);
+
 	writeln!(w, "\t\t{lower} = {{")?;
 
-	for Export { name, index, .. } in list.iter().filter(|v| External::from(v.kind) == wanted) {
+	for Export { name, index, .. } in list {
 		write!(w, "\t\t\t")?;
 		writeln!(w, r#"["{name}"] = {upper}[{index}],"#)?;
 	}
@@ -123,6 +157,7 @@ fn write_memory_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
 	let offset = wasm.import_count(External::Memory);
 	let memory = wasm.memory_section();
 
+	// Shared memories are allocated as any other, as only one thread ever sees them.
 	for (i, ty) in memory.iter().enumerate() {
 		let index = offset + i;
 		let min = ty.initial;
@@ -149,40 +184,74 @@ fn write_global_list(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) ->
 	Ok(())
 }
 
+fn write_element_items(element: &Element, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	write!(w, "{{ ")?;
+
+	match element.items.clone() {
+		ElementItems::Functions(functions) => {
+			for index in functions {
+				let index = index.unwrap();
+				write!(w, "FUNC_LIST[{index}],")?;
+			}
+		}
+		ElementItems::Expressions(expressions) => {
+			for init in expressions {
+				let init = init.unwrap();
+				write_constant(&init, type_info, w)?;
+				write!(w, ",")?;
+			}
+		}
+	}
+
+	write!(w, " }}")
+}
+
+fn element_item_count(element: &Element) -> u32 {
+	match &element.items {
+		ElementItems::Functions(functions) => functions.count(),
+		ElementItems::Expressions(expressions) => expressions.count(),
+	}
+}
+
 fn write_element_list(list: &[Element], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for element in list {
-		let ElementKind::Active { table_index: index, offset_expr: init } = element.kind else {
-			unimplemented!("passive elements not supported")
+	for (i, element) in list.iter().enumerate() {
+		let (index, init) = match element.kind {
+			ElementKind::Active {
+				table_index,
+				offset_expr,
+			} => (table_index, offset_expr),
+			// Declarations only make functions eligible for `ref.func`.
+			ElementKind::Declared => continue,
+			// Passive segments are kept aside until `table.init` copies them.
+			ElementKind::Passive => {
+				write!(w, "\tELEM_LIST[{i}] = ")?;
+				write_element_items(element, type_info, w)?;
+				writeln!(w)?;
+
+				continue;
+			}
 		};
 
 		let index = index.unwrap_or(0);
+		let len = element_item_count(element);
 
 		writeln!(w, "\tdo")?;
-		writeln!(w, "\t\tlocal target = TABLE_LIST[{index}].data")?;
+		writeln!(w, "\t\tlocal target = TABLE_LIST[{index}]")?;
 		write!(w, "\t\tlocal offset = ")?;
 
 		write_constant(&init, type_info, w)?;
 
 		writeln!(w)?;
-		write!(w, "\t\tlocal data = {{ ")?;
-
-		match element.items.clone() {
-			ElementItems::Functions(functions) => {
-				for index in functions {
-					let index = index.unwrap();
-					write!(w, "FUNC_LIST[{index}],")?;
-				}
-			}
-			ElementItems::Expressions(expressions) => {
-				for init in expressions {
-					let init = init.unwrap();
-					write_constant(&init, type_info, w)?;
-				}
-			}
-		}
-
-		writeln!(w, " }}")?;
-		writeln!(w, "\t\ttable.move(data, 1, #data, offset, target)")?;
+		write!(w, "\t\tlocal data = ")?;
+		write_element_items(element, type_info, w)?;
+		writeln!(w)?;
+		writeln!(w, "\t\tif offset < 0 or offset + {len} > target.min then")?;
+		writeln!(
+			w,
+			"\t\t\trt.trap.raise(\"out of bounds table access\", \"element segment {i} does not fit\")"
+		)?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\t\ttable.move(data, 1, {len}, offset, target.data)")?;
 		writeln!(w, "\tend")?;
 	}
 
@@ -190,9 +259,14 @@ fn write_element_list(list: &[Element], type_info: &TypeInfo, w: &mut dyn Write)
 }
 
 fn write_data_list(list: &[Data], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for data in list {
+	for (i, data) in list.iter().enumerate() {
 		let (index, init) = match data.kind {
-			DataKind::Passive => unimplemented!("passive data not supported"),
+			DataKind::Passive => {
+				write!(w, "\t")?;
+				writeln!(w, r#"DATA_LIST[{i}] = "{}""#, data.data.escape_ascii())?;
+
+				continue;
+			}
 			DataKind::Active {
 				memory_index,
 				offset_expr,
@@ -200,22 +274,40 @@ fn write_data_list(list: &[Data], type_info: &TypeInfo, w: &mut dyn Write) -> Re
 		};
 
 		write!(w, "\trt.store.string(MEMORY_LIST[{index}], ")?;
-		write_constant(&init, type_info, w)?;
+
+		// Memory64 offsets are `i64` cdata, which are unboxed into plain numbers.
+		if type_info.is_memory64(index.try_into().unwrap()) {
+			write!(w, "tonumber(")?;
+			write_constant(&init, type_info, w)?;
+			write!(w, ")")?;
+		} else {
+			write_constant(&init, type_info, w)?;
+		}
+
 		writeln!(w, r#","{}")"#, data.data.escape_ascii())?;
 	}
 
 	Ok(())
 }
 
-fn build_func_list(wasm: &Module, type_info: &TypeInfo) -> Vec<FuncData> {
+// Building each function only when it is needed keeps a single AST in memory,
+// at the cost of building every function once per pass.
+fn for_each_func<F>(wasm: &Module, type_info: &TypeInfo, mut func: F) -> Result<()>
+where
+	F: FnMut(usize, &FuncData) -> Result<()>,
+{
 	let offset = wasm.import_count(External::Func);
 	let mut builder = Factory::from_type_info(type_info);
 
-	wasm.code_section()
-		.iter()
-		.enumerate()
-		.map(|f| builder.create_indexed(f.0 + offset, f.1).unwrap())
-		.collect()
+	for (i, body) in wasm.code_section().iter().enumerate() {
+		let data = builder
+			.create_indexed(offset + i, body)
+			.map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+		func(offset + i, &data)?;
+	}
+
+	Ok(())
 }
 
 fn write_local_operation(head: &str, tail: &str, w: &mut dyn Write) -> Result<()> {
@@ -223,8 +315,9 @@ fn write_local_operation(head: &str, tail: &str, w: &mut dyn Write) -> Result<()
 
 	match (head, tail) {
 		("abs" | "ceil" | "floor" | "sqrt", _) => write!(w, "math.{head}"),
-		("rem", "i32") => write!(w, "math.fmod"),
 		("band" | "bor" | "bxor" | "bnot", _) => write!(w, "bit.{head}"),
+		// The `bit` library only looks at the low 5 or 6 bits of a shift count,
+		// which is exactly the masking Wasm asks for.
 		("shl", _) => write!(w, "bit.lshift"),
 		("shr", "i32" | "i64") => write!(w, "bit.arshift"),
 		("shr", "u32" | "u64") => write!(w, "bit.rshift"),
@@ -237,14 +330,22 @@ fn write_local_operation(head: &str, tail: &str, w: &mut dyn Write) -> Result<()
 	writeln!(w)
 }
 
-fn write_localize_used(func_list: &[FuncData], w: &mut dyn Write) -> Result<BTreeSet<usize>> {
+fn write_localize_used(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	w: &mut dyn Write,
+) -> Result<BTreeSet<usize>> {
 	let mut loc_set = BTreeSet::new();
 	let mut mem_set = BTreeSet::new();
 
-	for (loc, mem) in func_list.iter().map(localize::visit) {
+	for_each_func(wasm, type_info, |_, func| {
+		let (loc, mem) = localize::visit(func);
+
 		loc_set.extend(loc);
 		mem_set.extend(mem);
-	}
+
+		Ok(())
+	})?;
 
 	for loc in loc_set {
 		write_local_operation(loc.0, loc.1, w)?;
@@ -257,23 +358,35 @@ fn write_localize_used(func_list: &[FuncData], w: &mut dyn Write) -> Result<BTre
 	Ok(mem_set)
 }
 
+// Names may contain anything, so pick a long bracket level that they cannot close.
+fn write_func_name(name: &str, w: &mut dyn Write) -> Result<()> {
+	let mut level = String::new();
+
+	while name.contains(&format!("]{level}]")) {
+		level.push('=');
+	}
+
+	write!(w, "--[{level}[ {name} ]{level}] ")
+}
+
 fn write_func_start(wasm: &Module, index: u32, w: &mut dyn Write) -> Result<()> {
 	write!(w, "FUNC_LIST[{index}] = ")?;
 
 	wasm.name_section()
 		.get(&index)
-		.map_or_else(|| Ok(()), |name| write!(w, "--[[ {name} ]] "))
+		.map_or_else(|| Ok(()), |name| write_func_name(name, w))
 }
 
-fn write_func_list(wasm: &Module, func_list: &[FuncData], w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Func);
+fn write_func_list(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	for_each_func(wasm, type_info, |i, v| {
+		let index = i.try_into().unwrap();
 
-	func_list.iter().enumerate().try_for_each(|(i, v)| {
-		let index = (offset + i).try_into().unwrap();
+		let mut mng = Manager::function(v);
 
+		mng.set_function_index(i);
 		write_func_start(wasm, index, w)?;
 
-		v.write(&mut Manager::function(v), w)
+		v.write(&mut mng, w)
 	})
 }
 
@@ -309,6 +422,23 @@ fn write_module_start(
 	writeln!(w, "end")
 }
 
+/// Writes the runtime like [`crate::RUNTIME`], but with only the operations
+/// used by the module's functions, which is far smaller for most modules.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
+pub fn write_runtime_trimmed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+	let mut loc_set = BTreeSet::new();
+
+	for_each_func(wasm, type_info, |_, func| {
+		loc_set.extend(localize::visit(func).0);
+
+		Ok(())
+	})?;
+
+	runtime::write_used(&loc_set, w)
+}
+
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
 pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
@@ -317,24 +447,59 @@ pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write
 	ast.write(&mut Manager::function(&ast), w)
 }
 
+/// Reports which `rt` operations and memories the function at `index` in the
+/// function space would pull in, without writing any code for it.
+///
 /// # Errors
-/// Returns `Err` if writing to `Write` failed.
+/// Returns `Err` if `index` is out of range or names an import, or the function
+/// is malformed.
+pub fn function_dependencies(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	index: usize,
+) -> Result<Dependencies> {
+	let offset = wasm.import_count(External::Func);
+	let body = index
+		.checked_sub(offset)
+		.and_then(|i| wasm.code_section().get(i))
+		.ok_or_else(|| {
+			Error::new(
+				ErrorKind::InvalidInput,
+				format!("no body for function {index}"),
+			)
+		})?;
+
+	let data = Factory::from_type_info(type_info)
+		.create_indexed(index, body)
+		.map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+	let (runtime_set, memory_set) = localize::visit(&data);
+
+	Ok(Dependencies {
+		runtime_set,
+		memory_set,
+	})
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
 pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let func_list = build_func_list(wasm, type_info);
-	let mem_set = write_localize_used(&func_list, w)?;
+	let mem_set = write_localize_used(wasm, type_info, w)?;
 
 	writeln!(w, "local table_new = require(\"table.new\")")?;
 	write_named_array("FUNC_LIST", wasm.function_space(), w)?;
 	write_named_array("TABLE_LIST", wasm.table_space(), w)?;
 	write_named_array("MEMORY_LIST", wasm.memory_space(), w)?;
 	write_named_array("GLOBAL_LIST", wasm.global_space(), w)?;
+	write_named_array("DATA_LIST", wasm.data_section().len(), w)?;
+	write_named_array("ELEM_LIST", wasm.element_section().len(), w)?;
 
-	write_func_list(wasm, &func_list, w)?;
+	write_func_list(wasm, type_info, w)?;
 	write_module_start(wasm, type_info, &mem_set, w)
 }
 
 /// # Errors
-/// Returns `Err` if writing to `Write` failed.
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
 pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
 	let type_info = TypeInfo::from_module(wasm);
 
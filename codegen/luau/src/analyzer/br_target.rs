@@ -1,13 +1,18 @@
 use std::collections::HashMap;
 
 use wasm_ast::{
-	node::{Br, BrIf, BrTable, FuncData},
+	node::{Block, Br, BrIf, BrTable, FuncData, LabelType},
 	visit::{Driver, Visitor},
 };
 
+/// The targets of a `BrTable` followed by its default target.
+pub type TableData = (Vec<usize>, usize);
+
 struct Visit {
 	br_map: HashMap<usize, usize>,
+	br_list: Vec<TableData>,
 	has_branch: bool,
+	no_continue: bool,
 }
 
 impl Visit {
@@ -27,6 +32,13 @@ impl Visitor for Visit {
 		self.set_branch(stat.target());
 	}
 
+	// Without `continue`, even branches to the innermost loop go through `desired`.
+	fn visit_block(&mut self, block: &Block) {
+		if self.no_continue && block.label_type() == Some(LabelType::Backward) {
+			self.has_branch = true;
+		}
+	}
+
 	fn visit_br_table(&mut self, table: &BrTable) {
 		self.set_branch(table.default());
 
@@ -40,18 +52,22 @@ impl Visitor for Visit {
 
 		let id = std::ptr::from_ref(table) as usize;
 		let len = self.br_map.len() + 1;
+		let data = table.data().iter().map(|v| v.target()).collect();
 
 		self.br_map.insert(id, len);
+		self.br_list.push((data, table.default().target()));
 	}
 }
 
-pub fn visit(ast: &FuncData) -> (HashMap<usize, usize>, bool) {
+pub fn visit(ast: &FuncData, no_continue: bool) -> (HashMap<usize, usize>, Vec<TableData>, bool) {
 	let mut visit = Visit {
 		br_map: HashMap::new(),
+		br_list: Vec::new(),
 		has_branch: false,
+		no_continue,
 	};
 
 	ast.accept(&mut visit);
 
-	(visit.br_map, visit.has_branch)
+	(visit.br_map, visit.br_list, visit.has_branch)
 }
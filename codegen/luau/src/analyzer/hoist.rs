@@ -0,0 +1,45 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use wasm_ast::{
+	node::{Call, FuncData, GetGlobal, SetGlobal},
+	visit::{Driver, Visitor},
+};
+
+struct Visit {
+	global_map: BTreeMap<usize, usize>,
+	function_map: BTreeMap<usize, usize>,
+}
+
+impl Visitor for Visit {
+	fn visit_get_global(&mut self, v: GetGlobal) {
+		*self.global_map.entry(v.var()).or_default() += 1;
+	}
+
+	fn visit_set_global(&mut self, v: &SetGlobal) {
+		*self.global_map.entry(v.var()).or_default() += 1;
+	}
+
+	fn visit_call(&mut self, v: &Call) {
+		*self.function_map.entry(v.function()).or_default() += 1;
+	}
+}
+
+fn into_hot_set(map: BTreeMap<usize, usize>, threshold: usize) -> BTreeSet<usize> {
+	map.into_iter()
+		.filter_map(|(k, v)| (v > threshold).then_some(k))
+		.collect()
+}
+
+pub fn visit(ast: &FuncData, threshold: usize) -> (BTreeSet<usize>, BTreeSet<usize>) {
+	let mut visit = Visit {
+		global_map: BTreeMap::new(),
+		function_map: BTreeMap::new(),
+	};
+
+	ast.accept(&mut visit);
+
+	(
+		into_hot_set(visit.global_map, threshold),
+		into_hot_set(visit.function_map, threshold),
+	)
+}
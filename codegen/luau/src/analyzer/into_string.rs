@@ -50,64 +50,66 @@ pub trait IntoNameTuple {
 impl IntoNameTuple for UnOpType {
 	fn into_name_tuple(self) -> (&'static str, &'static str) {
 		match self {
+			Self::Eqz_I32 => ("rt.eqz", "i32"),
+			Self::Eqz_I64 => ("rt.eqz", "i64"),
 			Self::Clz_I32 => ("bit", "countlz"),
 			Self::Ctz_I32 => ("bit", "countrz"),
-			Self::Popcnt_I32 => ("rt_popcnt", "i32"),
-			Self::Clz_I64 => ("rt_clz", "i64"),
-			Self::Ctz_I64 => ("rt_ctz", "i64"),
-			Self::Popcnt_I64 => ("rt_popcnt", "i64"),
+			Self::Popcnt_I32 => ("rt.popcnt", "i32"),
+			Self::Clz_I64 => ("rt.clz", "i64"),
+			Self::Ctz_I64 => ("rt.ctz", "i64"),
+			Self::Popcnt_I64 => ("rt.popcnt", "i64"),
 			Self::Abs_F32 => ("math", "abs"),
-			Self::Neg_F32 => ("rt_neg", "f32"),
+			Self::Neg_F32 => ("rt.neg", "f32"),
 			Self::Ceil_F32 => ("math", "ceil"),
 			Self::Floor_F32 => ("math", "floor"),
-			Self::Truncate_F32 => ("rt_truncate", "f32"),
-			Self::Nearest_F32 => ("rt_nearest", "f32"),
+			Self::Truncate_F32 => ("rt.truncate", "f32"),
+			Self::Nearest_F32 => ("rt.nearest", "f32"),
 			Self::Sqrt_F32 => ("math", "sqrt"),
 			Self::Abs_F64 => ("math", "abs"),
-			Self::Neg_F64 => ("rt_neg", "f64"),
+			Self::Neg_F64 => ("rt.neg", "f64"),
 			Self::Ceil_F64 => ("math", "ceil"),
 			Self::Floor_F64 => ("math", "floor"),
-			Self::Truncate_F64 => ("rt_truncate", "f64"),
-			Self::Nearest_F64 => ("rt_nearest", "f64"),
+			Self::Truncate_F64 => ("rt.truncate", "f64"),
+			Self::Nearest_F64 => ("rt.nearest", "f64"),
 			Self::Sqrt_F64 => ("math", "sqrt"),
-			Self::Wrap_I32_I64 => ("rt_wrap", "i32_i64"),
-			Self::Truncate_I32_F32 => ("rt_truncate", "i32_f32"),
-			Self::Truncate_I32_F64 => ("rt_truncate", "u32_f64"),
-			Self::Truncate_U32_F32 => ("rt_truncate", "u32_f32"),
-			Self::Truncate_U32_F64 => ("rt_truncate", "u32_f64"),
-			Self::Truncate_I64_F32 => ("rt_truncate", "i64_f32"),
-			Self::Truncate_I64_F64 => ("rt_truncate", "i64_f64"),
-			Self::Truncate_U64_F32 => ("rt_truncate", "u64_f32"),
-			Self::Truncate_U64_F64 => ("rt_truncate", "u64_f64"),
-			Self::Saturate_I32_F32 => ("rt_saturate", "i32_f32"),
-			Self::Saturate_I32_F64 => ("rt_saturate", "i32_f64"),
-			Self::Saturate_U32_F32 => ("rt_saturate", "u32_f32"),
-			Self::Saturate_U32_F64 => ("rt_saturate", "u32_f64"),
-			Self::Saturate_I64_F32 => ("rt_saturate", "i64_f32"),
-			Self::Saturate_I64_F64 => ("rt_saturate", "i64_f64"),
-			Self::Saturate_U64_F32 => ("rt_saturate", "u64_f32"),
-			Self::Saturate_U64_F64 => ("rt_saturate", "u64_f64"),
-			Self::Extend_I32_N8 => ("rt_extend", "i32_n8"),
-			Self::Extend_I32_N16 => ("rt_extend", "i32_n16"),
-			Self::Extend_I64_N8 => ("rt_extend", "i64_n8"),
-			Self::Extend_I64_N16 => ("rt_extend", "i64_n16"),
-			Self::Extend_I64_N32 => ("rt_extend", "i64_n32"),
-			Self::Extend_I64_I32 => ("rt_extend", "i64_i32"),
-			Self::Extend_I64_U32 => ("rt_extend", "i64_u32"),
-			Self::Convert_F32_I32 => ("rt_convert", "f64_i32"),
+			Self::Wrap_I32_I64 => ("rt.wrap", "i32_i64"),
+			Self::Truncate_I32_F32 => ("rt.truncate", "i32_f32"),
+			Self::Truncate_I32_F64 => ("rt.truncate", "u32_f64"),
+			Self::Truncate_U32_F32 => ("rt.truncate", "u32_f32"),
+			Self::Truncate_U32_F64 => ("rt.truncate", "u32_f64"),
+			Self::Truncate_I64_F32 => ("rt.truncate", "i64_f32"),
+			Self::Truncate_I64_F64 => ("rt.truncate", "i64_f64"),
+			Self::Truncate_U64_F32 => ("rt.truncate", "u64_f32"),
+			Self::Truncate_U64_F64 => ("rt.truncate", "u64_f64"),
+			Self::Saturate_I32_F32 => ("rt.saturate", "i32_f64"),
+			Self::Saturate_I32_F64 => ("rt.saturate", "i32_f64"),
+			Self::Saturate_U32_F32 => ("rt.saturate", "u32_f64"),
+			Self::Saturate_U32_F64 => ("rt.saturate", "u32_f64"),
+			Self::Saturate_I64_F32 => ("rt.saturate", "i64_f64"),
+			Self::Saturate_I64_F64 => ("rt.saturate", "i64_f64"),
+			Self::Saturate_U64_F32 => ("rt.saturate", "u64_f64"),
+			Self::Saturate_U64_F64 => ("rt.saturate", "u64_f64"),
+			Self::Extend_I32_N8 => ("rt.extend", "i32_n8"),
+			Self::Extend_I32_N16 => ("rt.extend", "i32_n16"),
+			Self::Extend_I64_N8 => ("rt.extend", "i64_n8"),
+			Self::Extend_I64_N16 => ("rt.extend", "i64_n16"),
+			Self::Extend_I64_N32 => ("rt.extend", "i64_n32"),
+			Self::Extend_I64_I32 => ("rt.extend", "i64_i32"),
+			Self::Extend_I64_U32 => ("rt.extend", "i64_u32"),
+			Self::Convert_F32_I32 => ("rt.convert", "f64_i32"),
 			Self::Convert_F32_U32 => ("no", "op"),
-			Self::Convert_F32_I64 => ("rt_convert", "f64_i64"),
-			Self::Convert_F32_U64 => ("rt_convert", "f64_u64"),
+			Self::Convert_F32_I64 => ("rt.convert", "f64_i64"),
+			Self::Convert_F32_U64 => ("rt.convert", "f64_u64"),
 			Self::Demote_F32_F64 => ("no", "op"),
-			Self::Convert_F64_I32 => ("rt_convert", "f64_i32"),
+			Self::Convert_F64_I32 => ("rt.convert", "f64_i32"),
 			Self::Convert_F64_U32 => ("no", "op"),
-			Self::Convert_F64_I64 => ("rt_convert", "f64_i64"),
-			Self::Convert_F64_U64 => ("rt_convert", "f64_u64"),
+			Self::Convert_F64_I64 => ("rt.convert", "f64_i64"),
+			Self::Convert_F64_U64 => ("rt.convert", "f64_u64"),
 			Self::Promote_F64_F32 => ("no", "op"),
-			Self::Reinterpret_I32_F32 => ("rt_reinterpret", "i32_f32"),
-			Self::Reinterpret_I64_F64 => ("rt_reinterpret", "i64_f64"),
-			Self::Reinterpret_F32_I32 => ("rt_reinterpret", "f32_i32"),
-			Self::Reinterpret_F64_I64 => ("rt_reinterpret", "f64_i64"),
+			Self::Reinterpret_I32_F32 => ("rt.reinterpret", "i32_f32"),
+			Self::Reinterpret_I64_F64 => ("rt.reinterpret", "i64_f64"),
+			Self::Reinterpret_F32_I32 => ("rt.reinterpret", "f32_i32"),
+			Self::Reinterpret_F64_I64 => ("rt.reinterpret", "f64_i64"),
 		}
 	}
 }
@@ -115,50 +117,50 @@ impl IntoNameTuple for UnOpType {
 impl IntoNameTuple for BinOpType {
 	fn into_name_tuple(self) -> (&'static str, &'static str) {
 		match self {
-			Self::Add_I32 => ("rt_add", "i32"),
-			Self::Sub_I32 => ("rt_sub", "i32"),
-			Self::Mul_I32 => ("rt_mul", "i32"),
-			Self::DivS_I32 => ("rt_div", "i32"),
-			Self::DivU_I32 => ("rt_div", "u32"),
-			Self::RemS_I32 => ("rt_rem", "i32"),
-			Self::RemU_I32 => ("rt_rem", "u32"),
+			Self::Add_I32 => ("rt.add", "i32"),
+			Self::Sub_I32 => ("rt.sub", "i32"),
+			Self::Mul_I32 => ("rt.mul", "i32"),
+			Self::DivS_I32 => ("rt.div", "i32"),
+			Self::DivU_I32 => ("rt.div", "u32"),
+			Self::RemS_I32 => ("rt.rem", "i32"),
+			Self::RemU_I32 => ("rt.rem", "u32"),
 			Self::And_I32 => ("bit", "and"),
 			Self::Or_I32 => ("bit", "or"),
 			Self::Xor_I32 => ("bit", "xor"),
-			Self::Shl_I32 => ("rt_shl", "i32"),
-			Self::ShrS_I32 => ("rt_shr", "i32"),
-			Self::ShrU_I32 => ("rt_shr", "u32"),
-			Self::Rotl_I32 => ("rt_rotl", "i32"),
-			Self::Rotr_I32 => ("rt_rotr", "i32"),
-			Self::Add_I64 => ("rt_add", "i64"),
-			Self::Sub_I64 => ("rt_sub", "i64"),
-			Self::Mul_I64 => ("rt_mul", "i64"),
-			Self::DivS_I64 => ("rt_div", "i64"),
-			Self::DivU_I64 => ("rt_div", "u64"),
-			Self::RemS_I64 => ("rt_rem", "i64"),
-			Self::RemU_I64 => ("rt_rem", "u64"),
-			Self::And_I64 => ("rt_bit_and", "i64"),
-			Self::Or_I64 => ("rt_bit_or", "i64"),
-			Self::Xor_I64 => ("rt_bit_xor", "i64"),
-			Self::Shl_I64 => ("rt_shl", "i64"),
-			Self::ShrS_I64 => ("rt_shr", "i64"),
-			Self::ShrU_I64 => ("rt_shr", "u64"),
-			Self::Rotl_I64 => ("rt_rotl", "i64"),
-			Self::Rotr_I64 => ("rt_rotr", "i64"),
-			Self::Add_F32 => ("rt_add", "f32"),
-			Self::Sub_F32 => ("rt_sub", "f32"),
-			Self::Mul_F32 => ("rt_mul", "f32"),
-			Self::Div_F32 => ("rt_div", "f32"),
-			Self::Min_F32 => ("rt_min", "f32"),
-			Self::Max_F32 => ("rt_max", "f32"),
-			Self::Copysign_F32 => ("rt_copysign", "f32"),
-			Self::Add_F64 => ("rt_add", "f64"),
-			Self::Sub_F64 => ("rt_sub", "f64"),
-			Self::Mul_F64 => ("rt_mul", "f64"),
-			Self::Div_F64 => ("rt_div", "f64"),
-			Self::Min_F64 => ("rt_min", "f64"),
-			Self::Max_F64 => ("rt_max", "f64"),
-			Self::Copysign_F64 => ("rt_copysign", "f64"),
+			Self::Shl_I32 => ("rt.shl", "i32"),
+			Self::ShrS_I32 => ("rt.shr", "i32"),
+			Self::ShrU_I32 => ("rt.shr", "u32"),
+			Self::Rotl_I32 => ("rt.rotl", "i32"),
+			Self::Rotr_I32 => ("rt.rotr", "i32"),
+			Self::Add_I64 => ("rt.add", "i64"),
+			Self::Sub_I64 => ("rt.sub", "i64"),
+			Self::Mul_I64 => ("rt.mul", "i64"),
+			Self::DivS_I64 => ("rt.div", "i64"),
+			Self::DivU_I64 => ("rt.div", "u64"),
+			Self::RemS_I64 => ("rt.rem", "i64"),
+			Self::RemU_I64 => ("rt.rem", "u64"),
+			Self::And_I64 => ("rt.bit_and", "i64"),
+			Self::Or_I64 => ("rt.bit_or", "i64"),
+			Self::Xor_I64 => ("rt.bit_xor", "i64"),
+			Self::Shl_I64 => ("rt.shl", "i64"),
+			Self::ShrS_I64 => ("rt.shr", "i64"),
+			Self::ShrU_I64 => ("rt.shr", "u64"),
+			Self::Rotl_I64 => ("rt.rotl", "i64"),
+			Self::Rotr_I64 => ("rt.rotr", "i64"),
+			Self::Add_F32 => ("rt.add", "f32"),
+			Self::Sub_F32 => ("rt.sub", "f32"),
+			Self::Mul_F32 => ("rt.mul", "f32"),
+			Self::Div_F32 => ("rt.div", "f32"),
+			Self::Min_F32 => ("rt.min", "f32"),
+			Self::Max_F32 => ("rt.max", "f32"),
+			Self::Copysign_F32 => ("rt.copysign", "f32"),
+			Self::Add_F64 => ("rt.add", "f64"),
+			Self::Sub_F64 => ("rt.sub", "f64"),
+			Self::Mul_F64 => ("rt.mul", "f64"),
+			Self::Div_F64 => ("rt.div", "f64"),
+			Self::Min_F64 => ("rt.min", "f64"),
+			Self::Max_F64 => ("rt.max", "f64"),
+			Self::Copysign_F64 => ("rt.copysign", "f64"),
 		}
 	}
 }
@@ -166,38 +168,38 @@ impl IntoNameTuple for BinOpType {
 impl IntoNameTuple for CmpOpType {
 	fn into_name_tuple(self) -> (&'static str, &'static str) {
 		match self {
-			Self::Eq_I32 => ("rt_eq", "i32"),
-			Self::Ne_I32 => ("rt_ne", "i32"),
-			Self::LtS_I32 => ("rt_lt", "i32"),
-			Self::LtU_I32 => ("rt_lt", "u32"),
-			Self::GtS_I32 => ("rt_gt", "i32"),
-			Self::GtU_I32 => ("rt_gt", "u32"),
-			Self::LeS_I32 => ("rt_le", "i32"),
-			Self::LeU_I32 => ("rt_le", "u32"),
-			Self::GeS_I32 => ("rt_ge", "i32"),
-			Self::GeU_I32 => ("rt_ge", "u32"),
-			Self::Eq_I64 => ("rt_eq", "i64"),
-			Self::Ne_I64 => ("rt_ne", "i64"),
-			Self::LtS_I64 => ("rt_lt", "i64"),
-			Self::LtU_I64 => ("rt_lt", "u64"),
-			Self::GtS_I64 => ("rt_gt", "i64"),
-			Self::GtU_I64 => ("rt_gt", "u64"),
-			Self::LeS_I64 => ("rt_le", "i64"),
-			Self::LeU_I64 => ("rt_le", "u64"),
-			Self::GeS_I64 => ("rt_ge", "i64"),
-			Self::GeU_I64 => ("rt_ge", "u64"),
-			Self::Eq_F32 => ("rt_eq", "f32"),
-			Self::Ne_F32 => ("rt_ne", "f32"),
-			Self::Lt_F32 => ("rt_lt", "f32"),
-			Self::Gt_F32 => ("rt_gt", "f32"),
-			Self::Le_F32 => ("rt_le", "f32"),
-			Self::Ge_F32 => ("rt_ge", "f32"),
-			Self::Eq_F64 => ("rt_eq", "f64"),
-			Self::Ne_F64 => ("rt_ne", "f64"),
-			Self::Lt_F64 => ("rt_lt", "f64"),
-			Self::Gt_F64 => ("rt_gt", "f64"),
-			Self::Le_F64 => ("rt_le", "f64"),
-			Self::Ge_F64 => ("rt_ge", "f64"),
+			Self::Eq_I32 => ("rt.eq", "i32"),
+			Self::Ne_I32 => ("rt.ne", "i32"),
+			Self::LtS_I32 => ("rt.lt", "i32"),
+			Self::LtU_I32 => ("rt.lt", "u32"),
+			Self::GtS_I32 => ("rt.gt", "i32"),
+			Self::GtU_I32 => ("rt.gt", "u32"),
+			Self::LeS_I32 => ("rt.le", "i32"),
+			Self::LeU_I32 => ("rt.le", "u32"),
+			Self::GeS_I32 => ("rt.ge", "i32"),
+			Self::GeU_I32 => ("rt.ge", "u32"),
+			Self::Eq_I64 => ("rt.eq", "i64"),
+			Self::Ne_I64 => ("rt.ne", "i64"),
+			Self::LtS_I64 => ("rt.lt", "i64"),
+			Self::LtU_I64 => ("rt.lt", "u64"),
+			Self::GtS_I64 => ("rt.gt", "i64"),
+			Self::GtU_I64 => ("rt.gt", "u64"),
+			Self::LeS_I64 => ("rt.le", "i64"),
+			Self::LeU_I64 => ("rt.le", "u64"),
+			Self::GeS_I64 => ("rt.ge", "i64"),
+			Self::GeU_I64 => ("rt.ge", "u64"),
+			Self::Eq_F32 => ("rt.eq", "f32"),
+			Self::Ne_F32 => ("rt.ne", "f32"),
+			Self::Lt_F32 => ("rt.lt", "f32"),
+			Self::Gt_F32 => ("rt.gt", "f32"),
+			Self::Le_F32 => ("rt.le", "f32"),
+			Self::Ge_F32 => ("rt.ge", "f32"),
+			Self::Eq_F64 => ("rt.eq", "f64"),
+			Self::Ne_F64 => ("rt.ne", "f64"),
+			Self::Lt_F64 => ("rt.lt", "f64"),
+			Self::Gt_F64 => ("rt.gt", "f64"),
+			Self::Le_F64 => ("rt.le", "f64"),
+			Self::Ge_F64 => ("rt.ge", "f64"),
 		}
 	}
 }
@@ -207,6 +209,17 @@ pub trait TryIntoSymbol {
 	fn try_into_symbol(self) -> Option<&'static str>;
 }
 
+impl TryIntoSymbol for UnOpType {
+	fn try_into_symbol(self) -> Option<&'static str> {
+		let result = match self {
+			Self::Eqz_I32 | Self::Eqz_I64 => "==",
+			_ => return None,
+		};
+
+		Some(result)
+	}
+}
+
 impl TryIntoSymbol for BinOpType {
 	fn try_into_symbol(self) -> Option<&'static str> {
 		let result = match self {
@@ -214,7 +227,6 @@ impl TryIntoSymbol for BinOpType {
 			Self::Sub_F32 | Self::Sub_F64 => "-",
 			Self::Mul_F32 | Self::Mul_F64 => "*",
 			Self::Div_F32 | Self::Div_F64 => "/",
-			Self::RemU_I32 => "%",
 			_ => return None,
 		};
 
@@ -1,3 +1,5 @@
 pub mod br_target;
+pub mod hoist;
 pub mod into_string;
 pub mod localize;
+pub mod split;
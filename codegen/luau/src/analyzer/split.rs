@@ -0,0 +1,61 @@
+use std::ops::Range;
+
+use wasm_ast::{
+	node::{Expression, FuncData, Terminator},
+	visit::{Driver, Visitor},
+};
+
+#[derive(Default)]
+struct Visit {
+	size: usize,
+	has_tail_call: bool,
+}
+
+impl Visitor for Visit {
+	fn visit_expression(&mut self, _: &Expression) {
+		self.size += 1;
+	}
+
+	fn visit_terminator(&mut self, term: &Terminator) {
+		self.size += 1;
+		self.has_tail_call |= matches!(
+			term,
+			Terminator::ReturnCall(_) | Terminator::ReturnCallIndirect(_)
+		);
+	}
+}
+
+// Groups the top level statements into consecutive parts of roughly `limit`
+// expressions each, or none if the function is small enough or holds a tail
+// call that would only return from its part.
+pub fn visit(ast: &FuncData, limit: usize) -> Vec<Range<usize>> {
+	let code = ast.code().code();
+	let mut visit = Visit::default();
+	let mut split_list = Vec::new();
+	let mut start = 0;
+	let mut part = 0;
+
+	for (i, stat) in code.iter().enumerate() {
+		let last = visit.size;
+
+		stat.accept(&mut visit);
+
+		let size = visit.size - last;
+
+		if part != 0 && part + size > limit {
+			split_list.push(start..i);
+			start = i;
+			part = 0;
+		}
+
+		part += size;
+	}
+
+	split_list.push(start..code.len());
+
+	if visit.has_tail_call || split_list.len() == 1 {
+		split_list.clear();
+	}
+
+	split_list
+}
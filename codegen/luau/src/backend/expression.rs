@@ -4,35 +4,69 @@ use std::{
 };
 
 use wasm_ast::node::{
-	BinOp, CmpOp, Expression, GetGlobal, LoadAt, Local, MemorySize, Select, Temporary, UnOp, Value,
+	BinOp, BinOpType, CmpOp, Expression, GetGlobal, LoadAt, Local, MemorySize, RefFunc, Select,
+	TableGet, TableSize, Temporary, UnOp, UnOpType, Value,
 };
 
 use crate::analyzer::into_string::{IntoName, IntoNameTuple, TryIntoSymbol};
 
 use super::manager::{write_separated, Driver, Manager};
 
-macro_rules! impl_write_number {
-	($name:tt, $numeric:ty) => {
-		fn $name(number: $numeric, w: &mut dyn Write) -> Result<()> {
-			match (number.classify(), number.is_sign_negative()) {
-				(FpCategory::Nan, true) => write!(w, "(0.0 / 0.0)"),
-				(FpCategory::Nan, false) => write!(w, "-(0.0 / 0.0)"),
-				(FpCategory::Infinite, true) => write!(w, "-math.huge"),
-				(FpCategory::Infinite, false) => write!(w, "math.huge"),
-				_ => write!(w, "{number:e}"),
-			}
-		}
-	};
+// Only the canonical quiet NaN comes out of `0.0 / 0.0`, so any other payload
+// is rebuilt from its raw bits.
+fn write_f64(number: f64, w: &mut dyn Write) -> Result<()> {
+	let bits = number.to_bits();
+
+	if number.is_nan() && bits & 0x000F_FFFF_FFFF_FFFF != 0x0008_0000_0000_0000 {
+		let a = bits & 0xFFFF_FFFF;
+		let b = bits >> 32;
+
+		return write!(w, "rt.reinterpret_f64_i64(rt.i64_from_u32({a}, {b}))");
+	}
+
+	match (number.classify(), number.is_sign_negative()) {
+		(FpCategory::Nan, true) => write!(w, "(0.0 / 0.0)"),
+		(FpCategory::Nan, false) => write!(w, "-(0.0 / 0.0)"),
+		(FpCategory::Infinite, true) => write!(w, "-math.huge"),
+		(FpCategory::Infinite, false) => write!(w, "math.huge"),
+		_ => write!(w, "{number:e}"),
+	}
+}
+
+fn write_f32(number: f32, w: &mut dyn Write) -> Result<()> {
+	let bits = number.to_bits();
+
+	if number.is_nan() && bits & 0x007F_FFFF != 0x0040_0000 {
+		return write!(w, "rt.reinterpret_f32_i32({bits})");
+	}
+
+	// Every `f32` is exactly representable as an `f64`, so widening loses
+	// nothing and the shortest `f64` form reparses to the same `f32`.
+	write_f64(f64::from(number), w)
 }
 
 impl Driver for Select {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let mut last = self;
+
 		write!(w, "(if ")?;
-		Condition(self.condition()).write(mng, w)?;
-		write!(w, " then ")?;
-		self.on_true().write(mng, w)?;
+
+		// Chains of `select` in the false arm read as one `elseif` ladder.
+		loop {
+			Condition(last.condition()).write(mng, w)?;
+			write!(w, " then ")?;
+			last.on_true().write(mng, w)?;
+
+			let Expression::Select(next) = last.on_false() else {
+				break;
+			};
+
+			write!(w, " elseif ")?;
+			last = next;
+		}
+
 		write!(w, " else ")?;
-		self.on_false().write(mng, w)?;
+		last.on_false().write(mng, w)?;
 		write!(w, ")")
 	}
 }
@@ -62,8 +96,22 @@ impl Driver for Local {
 }
 
 impl Driver for GetGlobal {
-	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-		write!(w, "GLOBAL_LIST[{}].value", self.var())
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		if mng.hoist_global_set().contains(&self.var()) {
+			return write!(w, "global_{}.value", self.var());
+		}
+
+		write!(w, "{prefix}GLOBAL_LIST[{}].value", self.var())
+	}
+}
+
+impl Driver for RefFunc {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		write!(w, "{prefix}FUNC_LIST[{}]", self.function())
 	}
 }
 
@@ -71,8 +119,14 @@ impl Driver for LoadAt {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		let name = self.load_type().into_name();
 		let memory = self.memory();
+		let prefix = mng.prefix();
+
+		if mng.is_host_memory(memory) {
+			write!(w, "{prefix}memory_at_{memory}.read_{name}(")?;
+		} else {
+			write!(w, "rt.load_{name}({prefix}memory_at_{memory}, ")?;
+		}
 
-		write!(w, "rt_load_{name}(memory_at_{memory}, ")?;
 		self.pointer().write(mng, w)?;
 
 		if self.offset() != 0 {
@@ -84,8 +138,10 @@ impl Driver for LoadAt {
 }
 
 impl Driver for MemorySize {
-	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-		write!(w, "rt_allocator_size(memory_at_{})", self.memory())
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		write!(w, "rt.allocator_size({prefix}memory_at_{})", self.memory())
 	}
 }
 
@@ -97,20 +153,35 @@ pub fn write_i32(number: i32, w: &mut dyn Write) -> Result<()> {
 
 fn write_i64(number: i64, w: &mut dyn Write) -> Result<()> {
 	match number {
-		0 => write!(w, "rt_i64_ZERO"),
-		1 => write!(w, "rt_i64_ONE"),
+		0 => write!(w, "rt.i64_ZERO"),
+		1 => write!(w, "rt.i64_ONE"),
 		_ => {
 			let list = number.to_ne_bytes();
 			let a = u32::from_ne_bytes(list[0..4].try_into().unwrap());
 			let b = u32::from_ne_bytes(list[4..8].try_into().unwrap());
 
-			write!(w, "rt_i64_from_u32({a}, {b})")
+			write!(w, "rt.i64_from_u32({a}, {b})")
 		}
 	}
 }
 
-impl_write_number!(write_f32, f32);
-impl_write_number!(write_f64, f64);
+impl Driver for TableGet {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		write!(w, "rt.table_get({prefix}TABLE_LIST[{}], ", self.table())?;
+		self.index().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for TableSize {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		write!(w, "{prefix}TABLE_LIST[{}].min", self.table())
+	}
+}
 
 impl Driver for Value {
 	fn write(&self, _mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
@@ -123,18 +194,71 @@ impl Driver for Value {
 	}
 }
 
+struct UnOpBoolean<'a>(&'a UnOp, &'static str);
+
+impl Driver for UnOpBoolean<'_> {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let zero = match self.0.op_type() {
+			UnOpType::Eqz_I64 => "rt.i64_ZERO",
+			_ => "0",
+		};
+
+		self.0.rhs().write(mng, w)?;
+		write!(w, " {} {zero}", self.1)
+	}
+}
+
+const fn is_inexact_f32(op_type: UnOpType) -> bool {
+	matches!(
+		op_type,
+		UnOpType::Sqrt_F32
+			| UnOpType::Convert_F32_I32
+			| UnOpType::Convert_F32_U32
+			| UnOpType::Convert_F32_I64
+			| UnOpType::Convert_F32_U64
+			| UnOpType::Demote_F32_F64
+	)
+}
+
 impl Driver for UnOp {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if let Some(symbol) = self.op_type().try_into_symbol() {
+			write!(w, "(if ")?;
+			UnOpBoolean(self, symbol).write(mng, w)?;
+			return write!(w, " then 1 else 0)");
+		}
+
+		let demote = mng.options().strict_f32 && is_inexact_f32(self.op_type());
 		let (a, b) = self.op_type().into_name_tuple();
 
+		if demote {
+			write!(w, "rt.demote_f32_f64(")?;
+		}
+
 		write!(w, "{a}_{b}(")?;
 		self.rhs().write(mng, w)?;
-		write!(w, ")")
+		write!(w, ")")?;
+
+		if demote {
+			write!(w, ")")?;
+		}
+
+		Ok(())
 	}
 }
 
 impl Driver for BinOp {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let demote = mng.options().strict_f32
+			&& matches!(
+				self.op_type(),
+				BinOpType::Add_F32 | BinOpType::Sub_F32 | BinOpType::Mul_F32 | BinOpType::Div_F32
+			);
+
+		if demote {
+			write!(w, "rt.demote_f32_f64(")?;
+		}
+
 		if let Some(symbol) = self.op_type().try_into_symbol() {
 			write!(w, "(")?;
 			self.lhs().write(mng, w)?;
@@ -148,7 +272,13 @@ impl Driver for BinOp {
 		}
 
 		self.rhs().write(mng, w)?;
-		write!(w, ")")
+		write!(w, ")")?;
+
+		if demote {
+			write!(w, ")")?;
+		}
+
+		Ok(())
 	}
 }
 
@@ -182,12 +312,22 @@ impl Driver for CmpOp {
 	}
 }
 
+fn unary_symbol(data: &Expression) -> Option<(&UnOp, &'static str)> {
+	let Expression::UnOp(node) = data else {
+		return None;
+	};
+
+	node.op_type().try_into_symbol().map(|v| (node, v))
+}
+
 pub struct Condition<'a>(pub &'a Expression);
 
 impl Driver for Condition<'_> {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		if let Expression::CmpOp(node) = self.0 {
 			CmpOpBoolean(node).write(mng, w)
+		} else if let Some(symbol) = unary_symbol(self.0) {
+			UnOpBoolean(symbol.0, symbol.1).write(mng, w)
 		} else {
 			self.0.write(mng, w)?;
 			write!(w, " ~= 0")
@@ -202,8 +342,12 @@ impl Driver for Expression {
 			Self::GetTemporary(e) => e.write(mng, w),
 			Self::GetLocal(e) => e.write(mng, w),
 			Self::GetGlobal(e) => e.write(mng, w),
+			Self::RefFunc(e) => e.write(mng, w),
+			Self::RefNull => write!(w, "nil"),
 			Self::LoadAt(e) => e.write(mng, w),
 			Self::MemorySize(e) => e.write(mng, w),
+			Self::TableGet(e) => e.write(mng, w),
+			Self::TableSize(e) => e.write(mng, w),
 			Self::Value(e) => e.write(mng, w),
 			Self::UnOp(e) => e.write(mng, w),
 			Self::BinOp(e) => e.write(mng, w),
@@ -1,11 +1,18 @@
 use std::{
-	collections::HashMap,
+	collections::{BTreeSet, HashMap},
 	io::{Result, Write},
+	ops::Range,
 };
 
 use wasm_ast::node::{BrTable, FuncData, LabelType};
 
-use crate::analyzer::{br_target, localize};
+use crate::{
+	analyzer::{
+		br_target::{self, TableData},
+		hoist, localize, split,
+	},
+	options::Options,
+};
 
 #[macro_export]
 macro_rules! indentation {
@@ -50,53 +57,123 @@ fn get_pinned_registers(
 	(params + locals, temporaries)
 }
 
-pub struct Manager {
+pub struct Manager<'a> {
+	options: &'a Options,
+	function_index: Option<usize>,
+	num_memory_import: usize,
+	type_id_list: &'a [usize],
 	table_map: HashMap<usize, usize>,
+	table_list: Vec<TableData>,
 	has_branch: bool,
+	split_list: Vec<Range<usize>>,
+	hoist_global_set: BTreeSet<usize>,
+	hoist_function_set: BTreeSet<usize>,
 	num_local: usize,
 	num_temp: usize,
 	label_list: Vec<Option<LabelType>>,
+	loop_list: Vec<usize>,
 	indentation: usize,
 }
 
-impl Manager {
-	pub fn empty() -> Self {
+impl<'a> Manager<'a> {
+	pub fn empty(options: &'a Options) -> Self {
 		Self {
+			options,
+			function_index: None,
+			num_memory_import: 0,
+			type_id_list: &[],
 			table_map: HashMap::new(),
+			table_list: Vec::new(),
 			has_branch: false,
+			split_list: Vec::new(),
+			hoist_global_set: BTreeSet::new(),
+			hoist_function_set: BTreeSet::new(),
 			num_local: 0,
 			num_temp: usize::MAX,
 			label_list: Vec::new(),
+			loop_list: Vec::new(),
 			indentation: 0,
 		}
 	}
 
-	pub fn function(ast: &FuncData) -> Self {
+	pub fn function(ast: &FuncData, options: &'a Options) -> Self {
 		let (upvalues, memories) = localize::visit(ast);
-		let (table_map, has_branch) = br_target::visit(ast);
+		let (table_map, table_list, has_branch) = br_target::visit(ast, options.no_continue);
+		let split_list = options
+			.split_size
+			.map_or_else(Vec::new, |limit| split::visit(ast, limit));
+		let (hoist_global_set, hoist_function_set) = options
+			.hoist_threshold
+			.map_or_else(Default::default, |limit| hoist::visit(ast, limit));
 		let (num_local, num_temp) = get_pinned_registers(
-			upvalues.len() + memories.len(),
+			upvalues.len() + memories.len() + hoist_global_set.len() + hoist_function_set.len(),
 			ast.num_param(),
 			ast.local_data().len(),
 			ast.num_stack(),
 		);
 
 		Self {
+			options,
+			function_index: None,
+			num_memory_import: 0,
+			type_id_list: &[],
 			table_map,
+			table_list,
 			has_branch,
+			split_list,
+			hoist_global_set,
+			hoist_function_set,
 			num_local,
 			num_temp,
 			label_list: Vec::new(),
+			loop_list: Vec::new(),
 			indentation: 0,
 		}
 	}
 
+	pub const fn options(&self) -> &Options {
+		self.options
+	}
+
+	pub fn prefix(&self) -> &'a str {
+		&self.options.symbol_prefix
+	}
+
+	pub fn set_function_index(&mut self, index: usize) {
+		self.function_index = Some(index);
+	}
+
+	pub const fn function_index(&self) -> Option<usize> {
+		self.function_index
+	}
+
+	pub fn set_num_memory_import(&mut self, count: usize) {
+		self.num_memory_import = count;
+	}
+
+	pub fn set_type_id_list(&mut self, list: &'a [usize]) {
+		self.type_id_list = list;
+	}
+
+	// Lone instruction lists have no module to canonicalize against.
+	pub fn type_id(&self, index: usize) -> usize {
+		self.type_id_list.get(index).copied().unwrap_or(index)
+	}
+
+	pub const fn is_host_memory(&self, memory: usize) -> bool {
+		self.options.host_memory && memory < self.num_memory_import
+	}
+
 	pub fn get_table_index(&self, table: &BrTable) -> usize {
 		let id = std::ptr::from_ref(table) as usize;
 
 		self.table_map[&id]
 	}
 
+	pub fn table_list(&self) -> &[TableData] {
+		&self.table_list
+	}
+
 	pub fn has_table(&self) -> bool {
 		!self.table_map.is_empty()
 	}
@@ -105,6 +182,18 @@ impl Manager {
 		self.has_branch
 	}
 
+	pub fn split_list(&self) -> &[Range<usize>] {
+		&self.split_list
+	}
+
+	pub const fn hoist_global_set(&self) -> &BTreeSet<usize> {
+		&self.hoist_global_set
+	}
+
+	pub const fn hoist_function_set(&self) -> &BTreeSet<usize> {
+		&self.hoist_function_set
+	}
+
 	pub const fn num_local(&self) -> usize {
 		self.num_local
 	}
@@ -117,12 +206,26 @@ impl Manager {
 		&self.label_list
 	}
 
-	pub fn push_label(&mut self, label: Option<LabelType>) {
+	// Labels written without a `while` loop of their own cannot be broken out of,
+	// so branches and their checks land on the innermost label that has one.
+	pub fn push_label(&mut self, label: Option<LabelType>, has_loop: bool) {
+		if has_loop {
+			self.loop_list.push(self.label_list.len());
+		}
+
 		self.label_list.push(label);
 	}
 
 	pub fn pop_label(&mut self) {
 		self.label_list.pop().unwrap();
+
+		if self.loop_list.last() == Some(&self.label_list.len()) {
+			self.loop_list.pop();
+		}
+	}
+
+	pub fn loop_level(&self) -> Option<usize> {
+		self.loop_list.last().copied()
 	}
 
 	pub const fn indentation(&self) -> usize {
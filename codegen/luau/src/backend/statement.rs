@@ -1,16 +1,22 @@
 use std::{
-	io::{Result, Write},
+	io::{Error, ErrorKind, Result, Write},
 	ops::Range,
 };
 
 use wasm_ast::node::{
-	Block, Br, BrIf, BrTable, Call, CallIndirect, FuncData, If, LabelType, MemoryCopy, MemoryFill,
-	MemoryGrow, ResultList, SetGlobal, SetLocal, SetTemporary, Statement, StoreAt, Terminator,
+	AtomicCmpxchg, AtomicRmw, Block, Br, BrIf, BrTable, Call, CallIndirect, DataDrop, Expression,
+	FuncData, If, LabelType, LoadType, MemoryCopy, MemoryFill, MemoryGrow, MemoryInit, ResultList,
+	SetGlobal, SetLocal, SetTemporary, Statement, StoreAt, StoreType, TableGrow, TableSet,
+	Terminator,
 };
 use wasmparser::ValType;
 
 use crate::{
-	analyzer::into_string::IntoName, backend::manager::write_separated, indentation, indented, line,
+	analyzer::into_string::{IntoName, IntoNameTuple},
+	backend::manager::write_separated,
+	hook::Hook,
+	indentation, indented, line,
+	options::Options,
 };
 
 use super::{
@@ -34,15 +40,15 @@ impl Driver for Br {
 			writeln!(w)?;
 		}
 
-		if self.target() == 0 {
-			if mng.label_list().last() == Some(&Some(LabelType::Backward)) {
+		let level = mng.label_list().len() - 1 - self.target();
+
+		if mng.loop_level() == Some(level) && !is_desugared(mng, level) {
+			if mng.label_list()[level] == Some(LabelType::Backward) {
 				line!(mng, w, "continue")
 			} else {
 				line!(mng, w, "break")
 			}
 		} else {
-			let level = mng.label_list().len() - 1 - self.target();
-
 			line!(mng, w, "desired = {level}")?;
 			line!(mng, w, "break")
 		}
@@ -97,25 +103,39 @@ fn write_search_layer(
 	line!(mng, w, "end")
 }
 
-fn write_table_setup(table: &BrTable, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-	let id = mng.get_table_index(table);
+fn write_table_data(data: &[usize], default: usize, w: &mut dyn Write) -> Result<()> {
+	let num_default = data.iter().filter(|&&v| v == default).count();
 
-	line!(mng, w, "if not br_map[{id}] then")?;
-	mng.indent();
-	line!(mng, w, "br_map[{id}] = (function()")?;
+	// Tables that mostly hold the default only store the other entries,
+	// as missing ones fall back to the default on lookup anyway.
+	if num_default * 2 > data.len() {
+		data.iter()
+			.enumerate()
+			.filter(|v| *v.1 != default)
+			.try_for_each(|(i, v)| write!(w, "[{i}] = {v}, "))
+	} else {
+		write!(w, "[0] = ")?;
+
+		data.iter().try_for_each(|v| write!(w, "{v}, "))
+	}
+}
+
+fn write_table_list(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	line!(mng, w, "local br_map = {{")?;
 	mng.indent();
-	indented!(mng, w, "return {{ [0] = ")?;
 
-	table
-		.data()
-		.iter()
-		.try_for_each(|v| write!(w, "{}, ", v.target()))?;
+	for (i, (data, default)) in mng.table_list().iter().enumerate() {
+		indented!(mng, w, "[{}] = {{ ", i + 1)?;
+		write_table_data(data, *default, w)?;
+		writeln!(w, "}},")?;
+	}
 
-	writeln!(w, "}}")?;
 	mng.dedent();
-	line!(mng, w, "end)()")?;
-	mng.dedent();
-	line!(mng, w, "end")?;
+	line!(mng, w, "}}")
+}
+
+fn write_table_lookup(table: &BrTable, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let id = mng.get_table_index(table);
 
 	indented!(mng, w, "temp = br_map[{id}][")?;
 	table.condition().write(mng, w)?;
@@ -136,21 +156,67 @@ impl Driver for BrTable {
 		// and the appropriate jump is performed.
 		let list = to_ordered_table(self.data(), self.default());
 
-		write_table_setup(self, mng, w)?;
+		write_table_lookup(self, mng, w)?;
 		write_search_layer(0..list.len(), &list, mng, w)
 	}
 }
 
+// The function index tells apart traps raised by otherwise identical code.
+fn write_unreachable(mng: &Manager, w: &mut dyn Write) -> Result<()> {
+	indented!(mng, w, r#"rt.trap("unreachable", "out of code bounds"#)?;
+
+	if let Some(index) = mng.function_index() {
+		write!(w, " in function {index}")?;
+	}
+
+	writeln!(w, r#"")"#)
+}
+
 impl Driver for Terminator {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		match self {
-			Self::Unreachable => line!(mng, w, r#"error("out of code bounds")"#),
+			Self::Unreachable => write_unreachable(mng, w),
 			Self::Br(s) => s.write(mng, w),
 			Self::BrTable(s) => s.write(mng, w),
+			Self::ReturnCall(s) => {
+				indented!(mng, w, "do return ")?;
+				write_call_target(s, mng, w)?;
+				writeln!(w, " end")
+			}
+			Self::ReturnCallIndirect(s) => {
+				indentation!(mng, w)?;
+				write_call_indirect_check(s, mng, w)?;
+				write!(w, "do return ")?;
+				write_call_indirect_target(s, mng, w)?;
+				write!(w, " end")?;
+				write_call_indirect_end(mng, w)?;
+				writeln!(w)
+			}
 		}
 	}
 }
 
+// Loops written without `continue` are an inner loop holding the body inside an
+// outer one that repeats it, so branches back to the start break out of the inner
+// loop with `desired` set to the loop's own level.
+fn is_desugared(mng: &Manager, level: usize) -> bool {
+	mng.options().no_continue && mng.label_list()[level] == Some(LabelType::Backward)
+}
+
+fn write_loop_repeat(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let level = mng.label_list().len() - 1;
+
+	line!(mng, w, "if desired == {level} then")?;
+	mng.indent();
+	line!(mng, w, "desired = nil")?;
+	mng.dedent();
+	line!(mng, w, "else")?;
+	mng.indent();
+	line!(mng, w, "break")?;
+	mng.dedent();
+	line!(mng, w, "end")
+}
+
 fn write_br_parent(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 	if !mng.has_branch() || mng.label_list().iter().all(Option::is_none) {
 		return Ok(());
@@ -159,9 +225,10 @@ fn write_br_parent(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 	line!(mng, w, "if desired then")?;
 	mng.indent();
 
-	if let Some(last) = *mng.label_list().last().unwrap() {
-		let level = mng.label_list().len() - 1;
+	let level = mng.loop_level().unwrap();
+	let label = mng.label_list()[level].filter(|_| !is_desugared(mng, level));
 
+	if let Some(last) = label {
 		line!(mng, w, "if desired == {level} then")?;
 		mng.indent();
 		line!(mng, w, "desired = nil")?;
@@ -180,14 +247,94 @@ fn write_br_parent(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 	line!(mng, w, "end")
 }
 
+fn write_hook<F>(mng: &Manager, w: &mut dyn Write, func: F) -> Result<()>
+where
+	F: FnOnce(&dyn Hook, Option<usize>) -> Option<String>,
+{
+	let code = mng.options().hook.as_deref();
+	let code = code.and_then(|hook| func(hook, mng.function_index()));
+
+	code.map_or(Ok(()), |code| line!(mng, w, "{code}"))
+}
+
+fn write_stat_list(
+	block: &Block,
+	range: Range<usize>,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let code = &block.code()[range.clone()];
+	let offset_list = &block.offset_list()[range];
+
+	for (stat, offset) in code.iter().zip(offset_list) {
+		if mng.options().source_map {
+			line!(mng, w, "--@ 0x{offset:x}")?;
+		}
+
+		write_hook(mng, w, |hook, function| {
+			hook.before_statement(function, stat)
+		})?;
+		stat.write(mng, w)?;
+		write_hook(mng, w, |hook, function| {
+			hook.after_statement(function, stat)
+		})?;
+	}
+
+	Ok(())
+}
+
+// Each part runs in its own closure sharing the function's locals, and returns
+// `true` when it branched out of the function body so that the caller follows.
+fn write_split_list(block: &Block, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	for range in mng.split_list().to_vec() {
+		line!(mng, w, "if (function()")?;
+		mng.indent();
+		line!(mng, w, "while true do")?;
+		mng.indent();
+
+		write_stat_list(block, range, mng, w)?;
+
+		line!(mng, w, "return")?;
+		mng.dedent();
+		line!(mng, w, "end")?;
+		line!(mng, w, "return true")?;
+		mng.dedent();
+		line!(mng, w, "end)() then")?;
+		mng.indent();
+		line!(mng, w, "break")?;
+		mng.dedent();
+		line!(mng, w, "end")?;
+	}
+
+	Ok(())
+}
+
 impl Driver for Block {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-		mng.push_label(self.label_type());
+		let is_split = mng.label_list().is_empty() && !mng.split_list().is_empty();
+
+		mng.push_label(self.label_type(), true);
+
+		let level = mng.label_list().len() - 1;
+		let is_desugared = is_desugared(mng, level);
+
+		if is_desugared {
+			line!(mng, w, "while true do")?;
+			mng.indent();
+		}
 
 		line!(mng, w, "while true do")?;
 		mng.indent();
 
-		self.code().iter().try_for_each(|s| s.write(mng, w))?;
+		if self.label_type() == Some(LabelType::Backward) {
+			write_hook(mng, w, |hook, function| hook.loop_entry(function, level))?;
+		}
+
+		if is_split {
+			write_split_list(self, mng, w)?;
+		} else {
+			write_stat_list(self, 0..self.code().len(), mng, w)?;
+		}
 
 		match self.last() {
 			Some(v) => v.write(mng, w)?,
@@ -197,6 +344,12 @@ impl Driver for Block {
 		mng.dedent();
 		line!(mng, w, "end")?;
 
+		if is_desugared {
+			write_loop_repeat(mng, w)?;
+			mng.dedent();
+			line!(mng, w, "end")?;
+		}
+
 		mng.pop_label();
 		write_br_parent(mng, w)
 	}
@@ -214,6 +367,25 @@ impl Driver for BrIf {
 	}
 }
 
+// Arms that are never branched to need no loop to break out of, so their code
+// is written straight into the `if`.
+fn write_if_arm(block: &Block, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	if block.label_type().is_some() {
+		return block.write(mng, w);
+	}
+
+	mng.push_label(None, false);
+	write_stat_list(block, 0..block.code().len(), mng, w)?;
+
+	if let Some(v) = block.last() {
+		v.write(mng, w)?;
+	}
+
+	mng.pop_label();
+
+	Ok(())
+}
+
 impl Driver for If {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		indented!(mng, w, "if ")?;
@@ -221,13 +393,13 @@ impl Driver for If {
 		writeln!(w, " then")?;
 
 		mng.indent();
-		self.on_true().write(mng, w)?;
+		write_if_arm(self.on_true(), mng, w)?;
 		mng.dedent();
 
 		if let Some(v) = self.on_false() {
 			line!(mng, w, "else")?;
 			mng.indent();
-			v.write(mng, w)?;
+			write_if_arm(v, mng, w)?;
 			mng.dedent();
 		}
 
@@ -235,6 +407,20 @@ impl Driver for If {
 	}
 }
 
+fn write_call_target(call: &Call, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let function = call.function();
+	let prefix = mng.prefix();
+
+	if mng.hoist_function_set().contains(&function) {
+		write!(w, "func_{function}(")?;
+	} else {
+		write!(w, "{prefix}FUNC_LIST[{function}](")?;
+	}
+
+	call.param_list().write(mng, w)?;
+	write!(w, ")")
+}
+
 impl Driver for Call {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		if !self.result_list().is_empty() {
@@ -242,24 +428,118 @@ impl Driver for Call {
 			write!(w, " = ")?;
 		}
 
-		write!(w, "FUNC_LIST[{}](", self.function())?;
-		self.param_list().write(mng, w)?;
-		write!(w, ")")
+		write_call_target(self, mng, w)
 	}
 }
 
+const fn has_call_indirect_check(options: &Options) -> bool {
+	options.check_table_bounds || options.debug || options.check_signature
+}
+
+// Checks need the index more than once, so it is evaluated into a local after
+// the arguments, in the order the call itself would have evaluated them.
+fn write_call_indirect_check(
+	call: &CallIndirect,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let table = call.table();
+	let prefix = mng.prefix();
+
+	if !has_call_indirect_check(mng.options()) {
+		return Ok(());
+	}
+
+	writeln!(w, "do")?;
+	mng.indent();
+
+	if !call.param_list().is_empty() {
+		indented!(mng, w, "local ")?;
+		write_separated(0..call.param_list().len(), |i, w| write!(w, "arg_{i}"), w)?;
+		write!(w, " = ")?;
+		call.param_list().write(mng, w)?;
+		writeln!(w)?;
+	}
+
+	indented!(mng, w, "local index = ")?;
+	call.index().write(mng, w)?;
+	writeln!(w)?;
+
+	if mng.options().check_table_bounds {
+		line!(
+			mng,
+			w,
+			"rt.check_table_bounds({prefix}TABLE_LIST[{table}], index)"
+		)?;
+	}
+
+	// Direct calls take as many arguments as the callee's type says when they are
+	// built, so only indirect calls can disagree with their target.
+	if mng.options().debug {
+		let len = call.param_list().len();
+
+		line!(
+			mng,
+			w,
+			"rt.assert_arity({prefix}TABLE_LIST[{table}].data[index], {len})"
+		)?;
+	}
+
+	if mng.options().check_signature {
+		let id = mng.type_id(call.type_index());
+
+		line!(
+			mng,
+			w,
+			"rt.check_signature({prefix}FUNC_TYPE, {prefix}TABLE_LIST[{table}].data[index], {id})"
+		)?;
+	}
+
+	indentation!(mng, w)
+}
+
+fn write_call_indirect_end(mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	if !has_call_indirect_check(mng.options()) {
+		return Ok(());
+	}
+
+	writeln!(w)?;
+	mng.dedent();
+	indented!(mng, w, "end")
+}
+
+fn write_call_indirect_target(
+	call: &CallIndirect,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = mng.prefix();
+
+	write!(w, "{prefix}TABLE_LIST[{}].data[", call.table())?;
+
+	if has_call_indirect_check(mng.options()) {
+		write!(w, "index](")?;
+		write_separated(0..call.param_list().len(), |i, w| write!(w, "arg_{i}"), w)?;
+	} else {
+		call.index().write(mng, w)?;
+		write!(w, "](")?;
+		call.param_list().write(mng, w)?;
+	}
+
+	write!(w, ")")
+}
+
 impl Driver for CallIndirect {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		write_call_indirect_check(self, mng, w)?;
+
 		if !self.result_list().is_empty() {
 			self.result_list().write(mng, w)?;
 			write!(w, " = ")?;
 		}
 
-		write!(w, "TABLE_LIST[{}].data[", self.table())?;
-		self.index().write(mng, w)?;
-		write!(w, "](")?;
-		self.param_list().write(mng, w)?;
-		write!(w, ")")
+		write_call_indirect_target(self, mng, w)?;
+		write_call_indirect_end(mng, w)
 	}
 }
 
@@ -281,36 +561,101 @@ impl Driver for SetLocal {
 
 impl Driver for SetGlobal {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-		write!(w, "GLOBAL_LIST[{}].value = ", self.var())?;
+		let prefix = mng.prefix();
+
+		if mng.hoist_global_set().contains(&self.var()) {
+			write!(w, "global_{}.value = ", self.var())?;
+		} else {
+			write!(w, "{prefix}GLOBAL_LIST[{}].value = ", self.var())?;
+		}
+
 		self.value().write(mng, w)
 	}
 }
 
-impl Driver for StoreAt {
-	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-		let name = self.store_type().into_name();
-		let memory = self.memory();
+const fn store_width(store_type: StoreType) -> usize {
+	match store_type {
+		StoreType::I32_N8 | StoreType::I64_N8 => 1,
+		StoreType::I32_N16 | StoreType::I64_N16 => 2,
+		StoreType::I32 | StoreType::F32 | StoreType::I64_N32 => 4,
+		StoreType::I64 | StoreType::F64 => 8,
+	}
+}
+
+fn write_store_address(store: &StoreAt, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	store.pointer().write(mng, w)?;
 
-		write!(w, "rt_store_{name}(memory_at_{memory}, ")?;
+	if store.offset() != 0 {
+		write!(w, " + {}", store.offset())?;
+	}
+
+	Ok(())
+}
 
-		self.pointer().write(mng, w)?;
+fn write_store_call(
+	store: &StoreAt,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+	operands: impl FnOnce(&mut Manager, &mut dyn Write) -> Result<()>,
+) -> Result<()> {
+	let name = store.store_type().into_name();
+	let memory = store.memory();
+	let prefix = mng.prefix();
+
+	if mng.is_host_memory(memory) {
+		write!(w, "{prefix}memory_at_{memory}.write_{name}(")?;
+	} else {
+		write!(w, "rt.store_{name}({prefix}memory_at_{memory}, ")?;
+	}
 
-		if self.offset() != 0 {
-			write!(w, " + {}", self.offset())?;
+	operands(mng, w)?;
+	write!(w, ")")
+}
+
+// Audited stores bind their operands to locals first, so that the hook and the
+// store share them instead of evaluating each twice.
+fn write_audited_store(store: &StoreAt, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let width = store_width(store.store_type());
+
+	writeln!(w, "do")?;
+	mng.indent();
+
+	indented!(mng, w, "local address, value = ")?;
+	write_store_address(store, mng, w)?;
+	write!(w, ", ")?;
+	store.value().write(mng, w)?;
+	writeln!(w)?;
+
+	line!(mng, w, "rt.audit_store(address, {width}, value)")?;
+	indentation!(mng, w)?;
+	write_store_call(store, mng, w, |_, w| write!(w, "address, value"))?;
+	writeln!(w)?;
+
+	mng.dedent();
+	indented!(mng, w, "end")
+}
+
+impl Driver for StoreAt {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if mng.options().audit_store {
+			return write_audited_store(self, mng, w);
 		}
 
-		write!(w, ", ")?;
-		self.value().write(mng, w)?;
-		write!(w, ")")
+		write_store_call(self, mng, w, |mng, w| {
+			write_store_address(self, mng, w)?;
+			write!(w, ", ")?;
+			self.value().write(mng, w)
+		})
 	}
 }
 
 impl Driver for MemoryGrow {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		let memory = self.memory();
+		let prefix = mng.prefix();
 
 		self.result().write(mng, w)?;
-		write!(w, " = rt_allocator_grow(memory_at_{memory}, ")?;
+		write!(w, " = rt.allocator_grow({prefix}memory_at_{memory}, ")?;
 		self.size().write(mng, w)?;
 		write!(w, ")")
 	}
@@ -320,10 +665,11 @@ impl Driver for MemoryCopy {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		let memory_1 = self.destination().memory();
 		let memory_2 = self.source().memory();
+		let prefix = mng.prefix();
 
-		write!(w, "rt_store_copy(memory_at_{memory_1}, ")?;
+		write!(w, "rt.store_copy({prefix}memory_at_{memory_1}, ")?;
 		self.destination().pointer().write(mng, w)?;
-		write!(w, ", memory_at_{memory_2}, ")?;
+		write!(w, ", {prefix}memory_at_{memory_2}, ")?;
 		self.source().pointer().write(mng, w)?;
 		write!(w, ", ")?;
 		self.size().write(mng, w)?;
@@ -334,17 +680,148 @@ impl Driver for MemoryCopy {
 impl Driver for MemoryFill {
 	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 		let memory = self.destination().memory();
+		let prefix = mng.prefix();
+
+		write!(w, "rt.store_fill({prefix}memory_at_{memory}, ")?;
+		self.destination().pointer().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for MemoryInit {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let memory = self.destination().memory();
+		let prefix = mng.prefix();
 
-		write!(w, "rt_store_fill(memory_at_{memory}, ")?;
+		write!(w, "rt.store_init({prefix}memory_at_{memory}, ")?;
 		self.destination().pointer().write(mng, w)?;
+		write!(w, ", {prefix}DATA_LIST[{}], ", self.data())?;
+		self.offset().write(mng, w)?;
 		write!(w, ", ")?;
 		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+impl Driver for DataDrop {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		write!(w, "{prefix}DATA_LIST[{}] = nil", self.data())
+	}
+}
+
+impl Driver for TableSet {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		write!(w, "rt.table_set({prefix}TABLE_LIST[{}], ", self.table())?;
+		self.index().write(mng, w)?;
 		write!(w, ", ")?;
 		self.value().write(mng, w)?;
 		write!(w, ")")
 	}
 }
 
+impl Driver for TableGrow {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let prefix = mng.prefix();
+
+		self.result().write(mng, w)?;
+		write!(w, " = rt.table_grow({prefix}TABLE_LIST[{}], ", self.table())?;
+		self.value().write(mng, w)?;
+		write!(w, ", ")?;
+		self.size().write(mng, w)?;
+		write!(w, ")")
+	}
+}
+
+fn write_atomic_start(
+	name: &str,
+	memory: usize,
+	pointer: &Expression,
+	offset: u32,
+	mng: &mut Manager,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = mng.prefix();
+
+	// The helpers are handed the runtime's own loads and stores, which host
+	// memories do not go through.
+	if mng.is_host_memory(memory) {
+		return Err(Error::new(
+			ErrorKind::Unsupported,
+			"atomic access to host memory",
+		));
+	}
+
+	write!(w, "rt.atomic.{name}({prefix}memory_at_{memory}, ")?;
+	pointer.write(mng, w)?;
+
+	if offset != 0 {
+		write!(w, " + {offset}")?;
+	}
+
+	Ok(())
+}
+
+fn write_atomic_width(load_type: LoadType, store_type: StoreType, w: &mut dyn Write) -> Result<()> {
+	let load = load_type.into_name();
+	let store = store_type.into_name();
+
+	write!(w, ", rt.load_{load}, rt.store_{store}")
+}
+
+impl Driver for AtomicRmw {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		let name = if self.op_type().is_some() {
+			"rmw"
+		} else {
+			"xchg"
+		};
+
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		write_atomic_start(name, self.memory(), self.pointer(), self.offset(), mng, w)?;
+		write!(w, ", ")?;
+		self.value().write(mng, w)?;
+		write_atomic_width(self.load_type(), self.store_type(), w)?;
+
+		if let Some(op_type) = self.op_type() {
+			let (head, tail) = op_type.into_name_tuple();
+
+			write!(w, ", {head}_{tail}")?;
+		}
+
+		write!(w, ")")
+	}
+}
+
+impl Driver for AtomicCmpxchg {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		self.result().write(mng, w)?;
+		write!(w, " = ")?;
+		write_atomic_start(
+			"cmpxchg",
+			self.memory(),
+			self.pointer(),
+			self.offset(),
+			mng,
+			w,
+		)?;
+		write!(w, ", ")?;
+		self.expected().write(mng, w)?;
+		write!(w, ", ")?;
+		self.replacement().write(mng, w)?;
+		write_atomic_width(self.load_type(), self.store_type(), w)?;
+		write!(w, ")")
+	}
+}
+
 fn write_stat(stat: &dyn Driver, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
 	indentation!(mng, w)?;
 	stat.write(mng, w)?;
@@ -366,12 +843,53 @@ impl Driver for Statement {
 			Self::MemoryGrow(s) => write_stat(s, mng, w),
 			Self::MemoryCopy(s) => write_stat(s, mng, w),
 			Self::MemoryFill(s) => write_stat(s, mng, w),
+			Self::MemoryInit(s) => write_stat(s, mng, w),
+			Self::DataDrop(s) => write_stat(s, mng, w),
+			Self::TableSet(s) => write_stat(s, mng, w),
+			Self::TableGrow(s) => write_stat(s, mng, w),
+			Self::AtomicRmw(s) => write_stat(s, mng, w),
+			Self::AtomicCmpxchg(s) => write_stat(s, mng, w),
+		}
+	}
+}
+
+const fn type_to_name(typ: ValType) -> &'static str {
+	match typ {
+		ValType::I32 | ValType::F32 | ValType::F64 => "number",
+		ValType::I64 => "Vector3",
+		_ => "any",
+	}
+}
+
+fn write_result_type(ast: &FuncData, w: &mut dyn Write) -> Result<()> {
+	match ast.result_data() {
+		[] if ast.num_result() != 0 => Ok(()),
+		[typ] => write!(w, ": {}", type_to_name(*typ)),
+		list => {
+			write!(w, ": (")?;
+			write_separated(list.iter(), |t, w| write!(w, "{}", type_to_name(*t)), w)?;
+			write!(w, ")")
 		}
 	}
 }
 
-fn write_parameter_list(ast: &FuncData, w: &mut dyn Write) -> Result<()> {
+fn write_parameter_list(ast: &FuncData, mng: &Manager, w: &mut dyn Write) -> Result<()> {
 	write!(w, "function(")?;
+
+	if mng.options().type_annotations {
+		let iter = ast.param_data().iter().enumerate();
+
+		write_separated(
+			iter,
+			|(i, t), w| write!(w, "loc_{i}: {}", type_to_name(*t)),
+			w,
+		)?;
+		write!(w, ")")?;
+		write_result_type(ast, w)?;
+
+		return writeln!(w);
+	}
+
 	write_separated(0..ast.num_param(), |i, w| write!(w, "loc_{i}"), w)?;
 	writeln!(w, ")")
 }
@@ -379,7 +897,7 @@ fn write_parameter_list(ast: &FuncData, w: &mut dyn Write) -> Result<()> {
 const fn type_to_zero(typ: ValType) -> &'static str {
 	match typ {
 		ValType::F32 | ValType::F64 => "0.0",
-		ValType::I64 => "rt_i64_ZERO",
+		ValType::I64 => "rt.i64_ZERO",
 		_ => "0",
 	}
 }
@@ -387,16 +905,39 @@ const fn type_to_zero(typ: ValType) -> &'static str {
 fn write_variable_list(ast: &FuncData, mng: &Manager, w: &mut dyn Write) -> Result<()> {
 	let mut locals = ast.local_data().iter().copied();
 	let num_local = mng.num_local() - ast.num_param();
+	let annotated = mng.options().type_annotations;
 
 	for (i, typ) in locals.by_ref().enumerate().take(num_local) {
 		let index = ast.num_param() + i;
 		let zero = type_to_zero(typ);
 
-		line!(mng, w, "local loc_{index} = {zero}")?;
+		if annotated {
+			let name = type_to_name(typ);
+
+			line!(mng, w, "local loc_{index}: {name} = {zero}")?;
+		} else {
+			line!(mng, w, "local loc_{index} = {zero}")?;
+		}
 	}
 
 	if locals.len() != 0 {
-		indented!(mng, w, "local loc_spill = {{ ")?;
+		indented!(mng, w, "local loc_spill")?;
+
+		if annotated {
+			// Spilled locals share one table, so it is only typed precisely when
+			// every one of them has the same type.
+			let mut names = locals.clone().map(type_to_name);
+			let first = names.next().unwrap();
+			let name = if names.all(|v| v == first) {
+				first
+			} else {
+				"any"
+			};
+
+			write!(w, ": {{ {name} }}")?;
+		}
+
+		write!(w, " = {{ ")?;
 
 		for typ in locals {
 			let zero = type_to_zero(typ);
@@ -407,48 +948,87 @@ fn write_variable_list(ast: &FuncData, mng: &Manager, w: &mut dyn Write) -> Resu
 		writeln!(w, "}}")?;
 	}
 
+	// Registers are reused by values of any type over the function, so they
+	// can only be typed as `any`.
+	let (reg_name, spill_name) = if annotated {
+		(": any", ": { any }")
+	} else {
+		("", "")
+	};
+
 	let mut temporaries = 0..ast.num_stack();
 
 	for i in temporaries.by_ref().take(mng.num_temp()) {
-		line!(mng, w, "local reg_{i}")?;
+		line!(mng, w, "local reg_{i}{reg_name}")?;
 	}
 
 	if !temporaries.is_empty() {
 		let len = temporaries.len();
 
-		line!(mng, w, "local reg_spill = table.create({len})")?;
+		line!(mng, w, "local reg_spill{spill_name} = table.create({len})")?;
 	}
 
 	Ok(())
 }
 
-impl Driver for FuncData {
-	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
-		mng.indent();
+fn write_func_body(ast: &FuncData, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+	let prefix = mng.prefix();
 
-		write_parameter_list(self, w)?;
-		write_variable_list(self, mng, w)?;
+	mng.indent();
 
-		if mng.has_branch() {
-			line!(mng, w, "local desired")?;
-		}
+	write_parameter_list(ast, mng, w)?;
+	write_variable_list(ast, mng, w)?;
 
-		if mng.has_table() {
-			line!(mng, w, "local br_map = {{}}")?;
-		}
+	for var in mng.hoist_global_set() {
+		line!(mng, w, "local global_{var} = {prefix}GLOBAL_LIST[{var}]")?;
+	}
+
+	for function in mng.hoist_function_set() {
+		line!(
+			mng,
+			w,
+			"local func_{function} = {prefix}FUNC_LIST[{function}]"
+		)?;
+	}
 
-		self.code().write(mng, w)?;
+	if mng.has_branch() {
+		line!(mng, w, "local desired")?;
+	}
 
-		if self.num_result() != 0 {
-			indented!(mng, w, "return ")?;
+	if mng.has_table() {
+		line!(mng, w, "local temp")?;
+	}
 
-			ResultList::new(0, self.num_result()).write(mng, w)?;
+	write_hook(mng, w, |hook, function| hook.function_entry(function))?;
+	ast.code().write(mng, w)?;
 
-			writeln!(w)?;
+	if ast.num_result() != 0 {
+		indented!(mng, w, "return ")?;
+
+		ResultList::new(0, ast.num_result()).write(mng, w)?;
+
+		writeln!(w)?;
+	}
+
+	mng.dedent();
+
+	line!(mng, w, "end")
+}
+
+impl Driver for FuncData {
+	fn write(&self, mng: &mut Manager, w: &mut dyn Write) -> Result<()> {
+		if !mng.has_table() {
+			return write_func_body(self, mng, w);
 		}
 
+		// Jump tables are built once in an enclosing closure so that
+		// executing a `BrTable` only has to index into them.
+		writeln!(w, "(function()")?;
+		mng.indent();
+		write_table_list(mng, w)?;
+		indented!(mng, w, "return ")?;
+		write_func_body(self, mng, w)?;
 		mng.dedent();
-
-		line!(mng, w, "end")
+		line!(mng, w, "end)()")
 	}
 }
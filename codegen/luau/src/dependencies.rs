@@ -0,0 +1,23 @@
+use std::collections::BTreeSet;
+
+/// What a single function needs from the runtime and the module, found without
+/// translating it.
+#[derive(Clone, Default)]
+pub struct Dependencies {
+	pub(crate) runtime_set: BTreeSet<(&'static str, &'static str)>,
+	pub(crate) memory_set: BTreeSet<usize>,
+}
+
+impl Dependencies {
+	/// Returns the `(category, name)` pairs of the runtime helpers used.
+	#[must_use]
+	pub const fn runtime_set(&self) -> &BTreeSet<(&'static str, &'static str)> {
+		&self.runtime_set
+	}
+
+	/// Returns the indices of the memories accessed.
+	#[must_use]
+	pub const fn memory_set(&self) -> &BTreeSet<usize> {
+		&self.memory_set
+	}
+}
@@ -0,0 +1,28 @@
+use wasm_ast::node::Statement;
+
+/// Extra code written at fixed points of every function, such as the counters
+/// of a profiler. Each method may return a line of Luau to write at that point,
+/// and receives the `FUNC_LIST` index of the function when it has one.
+pub trait Hook {
+	/// Called at the start of a function body, once its locals are declared.
+	fn function_entry(&self, _function: Option<usize>) -> Option<String> {
+		None
+	}
+
+	/// Called at the start of every loop iteration, which is where its back-edges
+	/// land, with the nesting level of the loop's label.
+	fn loop_entry(&self, _function: Option<usize>, _level: usize) -> Option<String> {
+		None
+	}
+
+	/// Called before a statement is written.
+	fn before_statement(&self, _function: Option<usize>, _stat: &Statement) -> Option<String> {
+		None
+	}
+
+	/// Called after a statement is written, so it is skipped at runtime whenever
+	/// the statement branches elsewhere.
+	fn after_statement(&self, _function: Option<usize>, _stat: &Statement) -> Option<String> {
+		None
+	}
+}
@@ -1,8 +1,24 @@
 pub static RUNTIME: &str = include_str!("../runtime/runtime.luau");
 pub static EXPORT_RUNTIME: &str = include_str!("../runtime/export_runtime.luau");
 
-pub use translator::{from_inst_list, from_module_typed, from_module_untyped};
+pub use dependencies::Dependencies;
+pub use hook::Hook;
+pub use manifest::ExportEntry;
+pub use options::{I64Export, Options};
+pub use stats::Stats;
+#[cfg(feature = "wat")]
+pub use translator::from_wat;
+pub use translator::{
+	export_manifest, from_inst_list, from_module_bundled, from_module_typed, from_module_untyped,
+	from_module_with_stats, function_dependencies, write_header, write_prelude,
+	write_runtime_module,
+};
 
 mod analyzer;
 mod backend;
+mod dependencies;
+mod hook;
+mod manifest;
+mod options;
+mod stats;
 mod translator;
@@ -0,0 +1,30 @@
+use wasm_ast::module::External;
+
+/// An export of the module, as found on the instance returned by the output.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ExportEntry {
+	pub(crate) name: String,
+	pub(crate) kind: External,
+	pub(crate) index: u32,
+}
+
+impl ExportEntry {
+	/// Returns the name the export is found under.
+	#[must_use]
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Returns the kind of item exported, which also picks the instance field
+	/// it is found in, such as `func_list` for functions.
+	#[must_use]
+	pub const fn kind(&self) -> External {
+		self.kind
+	}
+
+	/// Returns the index of the exported item within its index space.
+	#[must_use]
+	pub const fn index(&self) -> u32 {
+		self.index
+	}
+}
@@ -0,0 +1,114 @@
+use std::rc::Rc;
+
+use crate::hook::Hook;
+
+/// How `i64` results of exported functions are handed to the host.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum I64Export {
+	/// The runtime's own `Vector3` representation.
+	#[default]
+	Native,
+
+	/// Two numbers holding the low and high 32 bits.
+	Pair,
+
+	/// A single number, losing precision beyond 53 bits.
+	Number,
+
+	/// A decimal string of the signed value.
+	String,
+}
+
+/// Settings that change the shape of the generated code.
+#[derive(Clone, Default)]
+pub struct Options {
+	/// Emit extra runtime assertions useful when debugging the translation.
+	pub debug: bool,
+
+	/// Round every inexact `f32` operation back to single precision.
+	pub strict_f32: bool,
+
+	/// Expose every table and its current contents under `debug.table_list`.
+	pub expose_tables: bool,
+
+	/// Access imported memories through their own `read_*` and `write_*` functions
+	/// instead of the runtime buffer helpers, so the host can own their storage.
+	pub host_memory: bool,
+
+	/// Expose the start function as `__start` on the instance instead of calling it.
+	pub defer_start: bool,
+
+	/// Expose a `dispose` method on the instance that releases its memories,
+	/// tables, and passive data so they can be collected promptly.
+	pub dispose: bool,
+
+	/// Start the output with `--!nolint` so linters skip the generated code.
+	pub no_lint: bool,
+
+	/// Precede every statement with a `--@ 0x...` comment holding the byte offset
+	/// of the instruction it was translated from.
+	pub source_map: bool,
+
+	/// Report every store to `rt.audit_store` with its address, width, and value
+	/// before it happens, so the host can log or veto memory writes.
+	pub audit_store: bool,
+
+	/// Leave out functions that cannot be reached from the exports, the start
+	/// function, or any element segment, keeping their `FUNC_LIST` slots empty.
+	pub strip_unused: bool,
+
+	/// Split function bodies larger than this many expressions into closures of
+	/// about that size, keeping each within Luau's per-function limits.
+	pub split_size: Option<usize>,
+
+	/// Emit a `FUNC_IDS` table mapping function names and export names to their
+	/// `FUNC_LIST` index, also exposed as `func_ids` on the instance.
+	pub func_ids: bool,
+
+	/// Check the signature of every `call_indirect` target against the expected
+	/// type, trapping on a mismatch instead of calling with the wrong arity.
+	pub check_signature: bool,
+
+	/// Convert `i64` results of exported functions into a form plain Luau can use.
+	pub export_i64: I64Export,
+
+	/// Cache globals and functions used more than this many times in a function
+	/// as locals at its start, rather than indexing their lists on every use.
+	pub hoist_threshold: Option<usize>,
+
+	/// Return the exported function itself instead of an instance constructor when
+	/// the module imports nothing and exports only that function.
+	pub bare_function: bool,
+
+	/// Check the index of every `call_indirect` against the current table size,
+	/// trapping with "undefined element" instead of calling a missing slot.
+	pub check_table_bounds: bool,
+
+	/// Bind the runtime helpers from `require` of this expression, such as a module
+	/// written by `write_runtime_module`, instead of expecting the runtime inlined.
+	pub runtime_require: Option<String>,
+
+	/// Prepend this to the names of the module's own top-level locals, such as
+	/// `FUNC_LIST` and `memory_at_0`, so several outputs can share one scope.
+	pub symbol_prefix: String,
+
+	/// Annotate the parameters, locals, and results of generated functions with
+	/// their Luau types, so the output can be checked in strict mode.
+	pub type_annotations: bool,
+
+	/// Place every export directly on the returned instance, keyed by its name,
+	/// instead of in `func_list`, `table_list`, `memory_list`, and `global_list`.
+	pub flat_exports: bool,
+
+	/// Write each data segment as whichever is shorter of its escaped string and
+	/// its base64 form decoded by `rt.base64_decode`, shrinking mostly binary data.
+	pub compact_data: bool,
+
+	/// Write loops without `continue`, branching back to their start by breaking
+	/// out of an inner loop instead, for embedders that disable the keyword.
+	pub no_continue: bool,
+
+	/// Write the code returned by this hook at function entries, loop entries, and
+	/// around statements, to instrument the output without changing the crate.
+	pub hook: Option<Rc<dyn Hook>>,
+}
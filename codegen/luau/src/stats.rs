@@ -0,0 +1,70 @@
+use std::io::{Result, Write};
+
+/// Sizes of the generated output, gathered while it is being written.
+#[derive(Clone, Default)]
+pub struct Stats {
+	pub(crate) total: usize,
+	pub(crate) func_list: Vec<(usize, usize)>,
+	pub(crate) warning_list: Vec<String>,
+}
+
+impl Stats {
+	/// Returns the number of bytes written for the whole module.
+	#[must_use]
+	pub const fn total(&self) -> usize {
+		self.total
+	}
+
+	/// Returns the `FUNC_LIST` index and byte size of every written function.
+	#[must_use]
+	pub fn func_list(&self) -> &[(usize, usize)] {
+		&self.func_list
+	}
+
+	/// Returns the number of functions written.
+	#[must_use]
+	pub fn function_count(&self) -> usize {
+		self.func_list.len()
+	}
+
+	/// Returns the `FUNC_LIST` index and byte size of the largest function.
+	#[must_use]
+	pub fn largest_function(&self) -> Option<(usize, usize)> {
+		self.func_list.iter().copied().max_by_key(|v| v.1)
+	}
+
+	/// Returns notes about parts of the module that hosts may not expect.
+	#[must_use]
+	pub fn warning_list(&self) -> &[String] {
+		&self.warning_list
+	}
+}
+
+pub struct CountWriter<'a> {
+	inner: &'a mut dyn Write,
+	count: usize,
+}
+
+impl<'a> CountWriter<'a> {
+	pub fn new(inner: &'a mut dyn Write) -> Self {
+		Self { inner, count: 0 }
+	}
+
+	pub const fn count(&self) -> usize {
+		self.count
+	}
+}
+
+impl Write for CountWriter<'_> {
+	fn write(&mut self, buf: &[u8]) -> Result<usize> {
+		let written = self.inner.write(buf)?;
+
+		self.count += written;
+
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> Result<()> {
+		self.inner.flush()
+	}
+}
@@ -1,6 +1,6 @@
 use std::{
-	collections::BTreeSet,
-	io::{Result, Write},
+	collections::{BTreeMap, BTreeSet},
+	io::{Error, ErrorKind, Result, Write},
 };
 
 use wasm_ast::{
@@ -10,12 +10,16 @@ use wasm_ast::{
 };
 use wasmparser::{
 	ConstExpr, Data, DataKind, Element, ElementItems, ElementKind, Export, Import, Operator,
-	OperatorsReader, ValType,
+	OperatorsReader, Type, TypeRef, ValType,
 };
 
 use crate::{
 	analyzer::localize,
-	backend::manager::{Driver, Manager},
+	backend::manager::{write_separated, Driver, Manager},
+	dependencies::Dependencies,
+	manifest::ExportEntry,
+	options::{I64Export, Options},
+	stats::{CountWriter, Stats},
 };
 
 trait AsIEName {
@@ -40,71 +44,298 @@ fn reader_to_code(reader: OperatorsReader) -> Vec<Operator> {
 	parsed.unwrap()
 }
 
-fn write_named_array(name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
+// Lists are indexed from 0, which Luau keeps in the hash part, so the array part
+// only needs room for indices 1 through the highest one, `len - 1`.
+fn write_named_array(prefix: &str, name: &str, len: usize, w: &mut dyn Write) -> Result<()> {
 	let Some(len) = len.checked_sub(1) else {
 		return Ok(());
 	};
 
-	writeln!(w, "local {name} = table.create({len})")
+	writeln!(w, "local {prefix}{name} = table.create({len})")
 }
 
-fn write_constant(init: &ConstExpr, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+fn write_func_ids(wasm: &Module, prefix: &str, w: &mut dyn Write) -> Result<()> {
+	let mut id_map: BTreeMap<_, _> = wasm
+		.name_section()
+		.iter()
+		.map(|(&index, &name)| (name, index))
+		.collect();
+
+	// Export names win over debug names since tools are more likely to know them.
+	let export_list = wasm
+		.export_section()
+		.iter()
+		.filter(|v| External::from(v.kind) == External::Func);
+
+	for Export { name, index, .. } in export_list {
+		id_map.insert(name, *index);
+	}
+
+	writeln!(w, "local {prefix}FUNC_IDS = {{")?;
+
+	for (name, index) in id_map {
+		write!(w, "\t")?;
+		writeln!(w, r#"["{}"] = {index},"#, name.as_bytes().escape_ascii())?;
+	}
+
+	writeln!(w, "}}")
+}
+
+fn write_constant(
+	init: &ConstExpr,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
 	let code = reader_to_code(init.get_operators_reader());
 	let func = Factory::from_type_info(type_info).create_anonymous(&code);
 
 	if let Some(Statement::SetTemporary(stat)) = func.code().code().last() {
-		stat.value().write(&mut Manager::empty(), w)
+		stat.value().write(&mut Manager::empty(options), w)
 	} else {
 		writeln!(w, r#"error("Valueless constant")"#)
 	}
 }
 
-fn write_import_of(list: &[Import], wanted: External, w: &mut dyn Write) -> Result<()> {
+// Globals are kept in `{ value = ... }` tables so that mutable ones are shared
+// with the host, but immutable ones may be handed over as plain values.
+fn write_global_import(target: &str, mutable: bool, w: &mut dyn Write) -> Result<()> {
+	write!(w, "\t")?;
+
+	if mutable {
+		writeln!(
+			w,
+			r#"assert(type({target}) == "table", "mutable global import must be a table")"#
+		)
+	} else {
+		writeln!(
+			w,
+			r#"if type({target}) ~= "table" then {target} = {{ value = {target} }} end"#
+		)
+	}
+}
+
+fn write_import_of(
+	list: &[Import],
+	wanted: External,
+	prefix: &str,
+	w: &mut dyn Write,
+) -> Result<()> {
 	let lower = wanted.as_ie_name();
 	let upper = lower.to_uppercase();
 
-	for (i, Import { name, module, .. }) in list
+	for (i, Import { name, module, ty }) in list
 		.iter()
 		.filter(|v| External::from(v.ty) == wanted)
 		.enumerate()
 	{
 		write!(w, "\t")?;
-		writeln!(w, r#"{upper}[{i}] = wasm["{module}"].{lower}["{name}"]"#)?;
+		writeln!(
+			w,
+			r#"{prefix}{upper}[{i}] = wasm["{module}"].{lower}["{name}"]"#
+		)?;
+
+		if let TypeRef::Global(ty) = ty {
+			write_global_import(&format!("{prefix}{upper}[{i}]"), ty.mutable, w)?;
+		}
 	}
 
 	Ok(())
 }
 
-fn write_export_of(list: &[Export], wanted: External, w: &mut dyn Write) -> Result<()> {
-	let lower = wanted.as_ie_name();
-	let upper = lower.to_uppercase();
+type WriteValue<'a> = &'a dyn Fn(&str, u32, &mut dyn Write) -> Result<()>;
 
-	writeln!(w, "\t\t{lower} = {{")?;
+fn write_list_value(upper: &str, index: u32, w: &mut dyn Write) -> Result<()> {
+	write!(w, "{upper}[{index}]")
+}
 
-	for Export { name, index, .. } in list.iter().filter(|v| External::from(v.kind) == wanted) {
-		write!(w, "\t\t\t")?;
-		writeln!(w, r#"["{name}"] = {upper}[{index}],"#)?;
+// Sorted by name so that the output does not depend on declaration order.
+fn sorted_export_of<'a>(list: &'a [Export<'a>], wanted: External) -> Vec<&'a Export<'a>> {
+	let mut list: Vec<_> = list
+		.iter()
+		.filter(|v| External::from(v.kind) == wanted)
+		.collect();
+
+	list.sort_by_key(|v| v.name);
+	list
+}
+
+fn write_export_fields(
+	list: &[Export],
+	wanted: External,
+	prefix: &str,
+	write_value: WriteValue,
+	indentation: &str,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let upper = format!("{prefix}{}", wanted.as_ie_name().to_uppercase());
+
+	for Export { name, index, .. } in sorted_export_of(list, wanted) {
+		write!(w, "{indentation}")?;
+		write!(w, r#"["{name}"] = "#)?;
+		write_value(&upper, *index, w)?;
+		writeln!(w, ",")?;
 	}
 
+	Ok(())
+}
+
+fn write_export_of(
+	list: &[Export],
+	wanted: External,
+	prefix: &str,
+	write_value: WriteValue,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let lower = wanted.as_ie_name();
+
+	writeln!(w, "\t\t{lower} = {{")?;
+	write_export_fields(list, wanted, prefix, write_value, "\t\t\t", w)?;
 	writeln!(w, "\t\t}},")
 }
 
-fn write_import_list(list: &[Import], w: &mut dyn Write) -> Result<()> {
-	write_import_of(list, External::Func, w)?;
-	write_import_of(list, External::Table, w)?;
-	write_import_of(list, External::Memory, w)?;
-	write_import_of(list, External::Global, w)
+// Fields the instance may hold besides the exports themselves.
+const INSTANCE_FIELD_LIST: [&str; 5] = ["rt", "__start", "debug", "dispose", "func_ids"];
+
+// Export names are unique within a module, but a flat instance also shares its
+// keys with the fields written next to the exports.
+fn find_flat_collision<'a>(list: &[Export<'a>]) -> Option<&'a str> {
+	let mut seen = BTreeSet::new();
+
+	list.iter()
+		.map(|v| v.name)
+		.find(|name| INSTANCE_FIELD_LIST.contains(name) || !seen.insert(*name))
+}
+
+fn write_import_list(list: &[Import], prefix: &str, w: &mut dyn Write) -> Result<()> {
+	write_import_of(list, External::Func, prefix, w)?;
+	write_import_of(list, External::Table, prefix, w)?;
+	write_import_of(list, External::Memory, prefix, w)?;
+	write_import_of(list, External::Global, prefix, w)
+}
+
+fn write_i64_result(i: usize, export_i64: I64Export, w: &mut dyn Write) -> Result<()> {
+	match export_i64 {
+		I64Export::Native => write!(w, "result_{i}"),
+		I64Export::Pair => write!(w, "low_{i}, high_{i}"),
+		I64Export::Number => write!(w, "rt.convert_f64_i64(result_{i})"),
+		I64Export::String => write!(w, "rt.i64_into_string(result_{i})"),
+	}
 }
 
-fn write_export_list(list: &[Export], w: &mut dyn Write) -> Result<()> {
+// Exported functions returning `i64` are wrapped so the host receives the
+// chosen representation instead of the runtime's own.
+fn write_func_export(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	index: u32,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let ty = type_info.func_type_index(index.try_into().unwrap());
+	let Type::Func(ty) = &wasm.type_section()[ty] else {
+		unreachable!("type at func index must be a func type");
+	};
+
+	let prefix = &options.symbol_prefix;
+	let export_i64 = options.export_i64;
+	let result_list = ty.results();
+
+	if export_i64 == I64Export::Native || !result_list.contains(&ValType::I64) {
+		return write!(w, "{prefix}FUNC_LIST[{index}]");
+	}
+
+	writeln!(w, "function(...)")?;
+	write!(w, "\t\t\t\tlocal ")?;
+	write_separated(0..result_list.len(), |i, w| write!(w, "result_{i}"), w)?;
+	writeln!(w, " = {prefix}FUNC_LIST[{index}](...)")?;
+
+	for (i, ty) in result_list.iter().enumerate() {
+		if *ty == ValType::I64 && export_i64 == I64Export::Pair {
+			writeln!(
+				w,
+				"\t\t\t\tlocal low_{i}, high_{i} = rt.i64_into_u32(result_{i})"
+			)?;
+		}
+	}
+
+	write!(w, "\t\t\t\treturn ")?;
+	write_separated(
+		result_list.iter().enumerate(),
+		|(i, ty), w| {
+			if *ty == ValType::I64 {
+				write_i64_result(i, export_i64, w)
+			} else {
+				write!(w, "result_{i}")
+			}
+		},
+		w,
+	)?;
+	writeln!(w)?;
+	write!(w, "\t\t\tend")
+}
+
+fn write_export_list(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let list = wasm.export_section();
+	let write_func =
+		|_: &str, index, w: &mut dyn Write| write_func_export(wasm, type_info, options, index, w);
+
+	let prefix = &options.symbol_prefix;
+
 	writeln!(w, "{}", crate::EXPORT_RUNTIME)?;
-	write_export_of(list, External::Func, w)?;
-	write_export_of(list, External::Table, w)?;
-	write_export_of(list, External::Memory, w)?;
-	write_export_of(list, External::Global, w)
+
+	if options.flat_exports {
+		if let Some(name) = find_flat_collision(list) {
+			let message = format!("export `{name}` collides with another instance field");
+
+			return Err(Error::new(ErrorKind::InvalidData, message));
+		}
+
+		write_export_fields(list, External::Func, prefix, &write_func, "\t\t", w)?;
+		write_export_fields(list, External::Table, prefix, &write_list_value, "\t\t", w)?;
+		write_export_fields(list, External::Memory, prefix, &write_list_value, "\t\t", w)?;
+
+		return write_export_fields(list, External::Global, prefix, &write_list_value, "\t\t", w);
+	}
+
+	write_export_of(list, External::Func, prefix, &write_func, w)?;
+	write_export_of(list, External::Table, prefix, &write_list_value, w)?;
+	write_export_of(list, External::Memory, prefix, &write_list_value, w)?;
+	write_export_of(list, External::Global, prefix, &write_list_value, w)
+}
+
+/// Lists the exports of the module in the order they are written to the returned
+/// instance, grouped by kind and sorted by name within each kind.
+#[must_use]
+pub fn export_manifest(wasm: &Module) -> Vec<ExportEntry> {
+	let list = wasm.export_section();
+	let kind_list = [
+		External::Func,
+		External::Table,
+		External::Memory,
+		External::Global,
+	];
+
+	kind_list
+		.into_iter()
+		.flat_map(|kind| {
+			sorted_export_of(list, kind)
+				.into_iter()
+				.map(move |v| ExportEntry {
+					name: v.name.to_string(),
+					kind,
+					index: v.index,
+				})
+		})
+		.collect()
 }
 
-fn write_table_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+fn write_table_list(wasm: &Module, prefix: &str, w: &mut dyn Write) -> Result<()> {
 	let offset = wasm.import_count(External::Table);
 	let table = wasm.table_section();
 
@@ -115,226 +346,826 @@ fn write_table_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
 
 		writeln!(
 			w,
-			"\tTABLE_LIST[{index}] = {{ min = {min}, max = {max}, data = {{}} }}"
+			"\t{prefix}TABLE_LIST[{index}] = {{ min = {min}, max = {max}, data = {{}} }}"
 		)?;
 	}
 
 	Ok(())
 }
 
-fn write_memory_list(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+fn write_memory_list(wasm: &Module, prefix: &str, w: &mut dyn Write) -> Result<()> {
 	let offset = wasm.import_count(External::Memory);
 	let memory = wasm.memory_section();
 
+	// Shared memories are allocated as any other, as only one thread ever sees them.
 	for (i, ty) in memory.iter().enumerate() {
 		let index = offset + i;
 		let min = ty.initial;
 		let max = ty.maximum.unwrap_or(0xFFFF);
 
-		writeln!(w, "\tMEMORY_LIST[{index}] = rt_allocator_new({min}, {max})")?;
+		writeln!(
+			w,
+			"\t{prefix}MEMORY_LIST[{index}] = rt.allocator_new({min}, {max})"
+		)?;
 	}
 
 	Ok(())
 }
 
-fn write_global_list(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
+fn write_global_list(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
 	let offset = wasm.import_count(External::Global);
 	let global = wasm.global_section();
+	let prefix = &options.symbol_prefix;
 
 	for (i, global) in global.iter().enumerate() {
 		let index = offset + i;
 
-		write!(w, "\tGLOBAL_LIST[{index}] = {{ value = ")?;
-		write_constant(&global.init_expr, type_info, w)?;
+		write!(w, "\t{prefix}GLOBAL_LIST[{index}] = {{ value = ")?;
+		write_constant(&global.init_expr, type_info, options, w)?;
 		writeln!(w, " }}")?;
 	}
 
 	Ok(())
 }
 
-fn write_element_list(list: &[Element], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for element in list {
-		let ElementKind::Active {
-			table_index: index,
-			offset_expr: init,
-		} = element.kind
-		else {
-			unimplemented!("passive elements not supported")
+fn write_element_items(
+	element: &Element,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = &options.symbol_prefix;
+
+	write!(w, "{{ ")?;
+
+	match element.items.clone() {
+		ElementItems::Functions(functions) => {
+			for index in functions {
+				let index = index.unwrap();
+				write!(w, "{prefix}FUNC_LIST[{index}],")?;
+			}
+		}
+		ElementItems::Expressions(expressions) => {
+			for init in expressions {
+				let init = init.unwrap();
+				write_constant(&init, type_info, options, w)?;
+				write!(w, ",")?;
+			}
+		}
+	}
+
+	write!(w, " }}")
+}
+
+// Items may be `nil`, so the length of their Lua table cannot be trusted.
+fn element_item_count(element: &Element) -> u32 {
+	match &element.items {
+		ElementItems::Functions(functions) => functions.count(),
+		ElementItems::Expressions(expressions) => expressions.count(),
+	}
+}
+
+fn write_element_list(
+	list: &[Element],
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = &options.symbol_prefix;
+
+	for (i, element) in list.iter().enumerate() {
+		let (index, init) = match element.kind {
+			ElementKind::Active {
+				table_index,
+				offset_expr,
+			} => (table_index, offset_expr),
+			// Declarations only make functions eligible for `ref.func`.
+			ElementKind::Declared => continue,
+			// Passive segments are kept aside until `table.init` copies them.
+			ElementKind::Passive => {
+				write!(w, "\t{prefix}ELEM_LIST[{i}] = ")?;
+				write_element_items(element, type_info, options, w)?;
+				writeln!(w)?;
+
+				continue;
+			}
 		};
 
 		let index = index.unwrap_or(0);
+		let len = element_item_count(element);
 
-		writeln!(w, "\tdo")?;
-		writeln!(w, "\t\tlocal target = TABLE_LIST[{index}].data")?;
+		write!(w, "\t")?;
+		writeln!(w, r#"rt.init_segment("element segment {i}", function()"#)?;
+		writeln!(w, "\t\tlocal target = {prefix}TABLE_LIST[{index}]")?;
 		write!(w, "\t\tlocal offset = ")?;
 
-		write_constant(&init, type_info, w)?;
+		write_constant(&init, type_info, options, w)?;
 
 		writeln!(w)?;
-		write!(w, "\t\tlocal data = {{ ")?;
+		write!(w, "\t\tlocal data = ")?;
+		write_element_items(element, type_info, options, w)?;
+		writeln!(w)?;
+		writeln!(w, "\t\tif offset + {len} > target.min then")?;
+		writeln!(
+			w,
+			"\t\t\trt.trap(\"out of bounds table access\", \"{len} elements at \" .. offset .. \" are past size \" .. target.min)"
+		)?;
+		writeln!(w, "\t\tend")?;
+		writeln!(w, "\t\ttable.move(data, 1, {len}, offset, target.data)")?;
+		writeln!(w, "\tend)")?;
+	}
 
-		match element.items.clone() {
-			ElementItems::Functions(functions) => {
-				for index in functions {
-					let index = index.unwrap();
-					write!(w, "FUNC_LIST[{index}],")?;
-				}
-			}
-			ElementItems::Expressions(expressions) => {
-				for init in expressions {
-					let init = init.unwrap();
-					write_constant(&init, type_info, w)?;
-				}
-			}
-		}
+	Ok(())
+}
 
-		writeln!(w, " }}")?;
-		writeln!(w, "\t\ttable.move(data, 1, #data, offset, target)")?;
-		writeln!(w, "\tend")?;
+fn write_base64(data: &[u8], w: &mut dyn Write) -> Result<()> {
+	const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+	let mut encoded = Vec::with_capacity(data.len().div_ceil(3) * 4);
+
+	for chunk in data.chunks(3) {
+		let word = chunk
+			.iter()
+			.enumerate()
+			.fold(0, |acc, (i, &v)| acc | u32::from(v) << (16 - i * 8));
+
+		for i in 0..4 {
+			let digit = if i <= chunk.len() {
+				ALPHABET[usize::try_from(word >> (18 - i * 6) & 0x3F).unwrap()]
+			} else {
+				b'='
+			};
+
+			encoded.push(digit);
+		}
 	}
 
-	Ok(())
+	w.write_all(&encoded)
+}
+
+// Escaping costs up to four characters per byte while base64 costs a fixed four
+// per three, so mostly binary segments shrink while text stays readable.
+fn write_data_string(data: &[u8], options: &Options, w: &mut dyn Write) -> Result<()> {
+	let is_shorter = || {
+		let encoded = data.len().div_ceil(3) * 4 + "rt.base64_decode()".len();
+
+		encoded < data.escape_ascii().count()
+	};
+
+	if options.compact_data && is_shorter() {
+		write!(w, r#"rt.base64_decode(""#)?;
+		write_base64(data, w)?;
+		write!(w, r#"")"#)
+	} else {
+		write!(w, r#""{}""#, data.escape_ascii())
+	}
 }
 
-fn write_data_list(list: &[Data], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	for data in list {
+fn write_data_list(
+	list: &[Data],
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = &options.symbol_prefix;
+
+	for (i, data) in list.iter().enumerate() {
 		let (index, init) = match data.kind {
-			DataKind::Passive => unimplemented!("passive data not supported"),
+			DataKind::Passive => {
+				write!(w, "\t{prefix}DATA_LIST[{i}] = ")?;
+				write_data_string(data.data, options, w)?;
+				writeln!(w)?;
+
+				continue;
+			}
 			DataKind::Active {
 				memory_index,
 				offset_expr,
 			} => (memory_index, offset_expr),
 		};
 
-		write!(w, "\trt_store_string(MEMORY_LIST[{index}], ")?;
-		write_constant(&init, type_info, w)?;
-		writeln!(w, r#","{}")"#, data.data.escape_ascii())?;
+		write!(w, "\t")?;
+		write!(
+			w,
+			r#"rt.init_segment("data segment {i}", rt.store_string, "#
+		)?;
+		write!(w, "{prefix}MEMORY_LIST[{index}], ")?;
+
+		// Memory64 offsets are `i64` constants, which must become plain numbers.
+		if type_info.is_memory64(index.try_into().unwrap()) {
+			write!(w, "rt.convert_f64_u64(")?;
+			write_constant(&init, type_info, options, w)?;
+			write!(w, ")")?;
+		} else {
+			write_constant(&init, type_info, options, w)?;
+		}
+
+		write!(w, ",")?;
+		write_data_string(data.data, options, w)?;
+		writeln!(w, ")")?;
 	}
 
 	Ok(())
 }
 
-fn build_func_list(wasm: &Module, type_info: &TypeInfo) -> Vec<FuncData> {
+// Functions are built one at a time and dropped once handled, so that only one
+// function's AST is ever alive no matter how large the module is.
+fn for_each_func<F>(wasm: &Module, type_info: &TypeInfo, mut func: F) -> Result<()>
+where
+	F: FnMut(usize, &FuncData) -> Result<()>,
+{
 	let offset = wasm.import_count(External::Func);
 	let mut builder = Factory::from_type_info(type_info);
 
+	for (i, body) in wasm.code_section().iter().enumerate() {
+		let data = builder
+			.create_indexed(offset + i, body)
+			.map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+		func(offset + i, &data)?;
+	}
+
+	Ok(())
+}
+
+// Every memory gets its local up front, as the functions that capture them are
+// written before it is known which memories they use.
+fn write_localize_memory(mem_set: &BTreeSet<usize>, prefix: &str, w: &mut dyn Write) -> Result<()> {
+	for mem in mem_set {
+		writeln!(w, "local {prefix}memory_at_{mem}")?;
+	}
+
+	Ok(())
+}
+
+// Calls are read off the operators directly, so that finding the live functions
+// does not build each of them a second time.
+fn find_call_list(wasm: &Module) -> Result<Vec<BTreeSet<usize>>> {
+	let into_error = |e| Error::new(ErrorKind::InvalidData, e);
+
 	wasm.code_section()
 		.iter()
-		.enumerate()
-		.map(|f| builder.create_indexed(f.0 + offset, f.1).unwrap())
+		.map(|body| {
+			let mut call_set = BTreeSet::new();
+
+			for op in body.get_operators_reader().map_err(into_error)? {
+				match op.map_err(into_error)? {
+					Operator::Call { function_index }
+					| Operator::ReturnCall { function_index }
+					| Operator::RefFunc { function_index } => {
+						call_set.insert(function_index.try_into().unwrap());
+					}
+					_ => {}
+				}
+			}
+
+			Ok(call_set)
+		})
 		.collect()
 }
 
-fn write_localize_used(
-	wasm: &Module,
-	func_list: &[FuncData],
-	w: &mut dyn Write,
-) -> Result<BTreeSet<usize>> {
-	let mut loc_set = BTreeSet::new();
-	let mut mem_set = BTreeSet::new();
+fn const_function_list(init: &ConstExpr) -> Vec<usize> {
+	let code = reader_to_code(init.get_operators_reader());
+
+	code.into_iter()
+		.filter_map(|op| match op {
+			Operator::RefFunc { function_index } => function_index.try_into().ok(),
+			_ => None,
+		})
+		.collect()
+}
 
-	let has_global_i64 = wasm
-		.global_section()
+fn find_live_set(wasm: &Module, call_list: &[BTreeSet<usize>]) -> BTreeSet<usize> {
+	let offset = wasm.import_count(External::Func);
+	let mut pending: Vec<usize> = wasm
+		.export_section()
 		.iter()
-		.any(|g| g.ty.content_type == ValType::I64);
+		.filter(|v| External::from(v.kind) == External::Func)
+		.map(|v| v.index.try_into().unwrap())
+		.chain(wasm.start_section().map(|v| v.try_into().unwrap()))
+		.collect();
 
-	if has_global_i64 {
-		loc_set.insert(("i64", "ZERO"));
-		loc_set.insert(("i64", "ONE"));
-		loc_set.insert(("i64", "from_u32"));
+	for element in wasm.element_section() {
+		match element.items.clone() {
+			ElementItems::Functions(functions) => {
+				for index in functions {
+					pending.push(index.unwrap().try_into().unwrap());
+				}
+			}
+			ElementItems::Expressions(expressions) => {
+				for init in expressions {
+					pending.extend(const_function_list(&init.unwrap()));
+				}
+			}
+		}
 	}
 
-	for (loc, mem) in func_list.iter().map(localize::visit) {
-		loc_set.extend(loc);
-		mem_set.extend(mem);
+	for global in wasm.global_section() {
+		pending.extend(const_function_list(&global.init_expr));
+	}
+
+	let mut live_set = BTreeSet::new();
+
+	while let Some(index) = pending.pop() {
+		if !live_set.insert(index) {
+			continue;
+		}
+
+		if let Some(call_set) = index.checked_sub(offset).map(|v| &call_list[v]) {
+			pending.extend(call_set);
+		}
 	}
 
-	for mem in &mem_set {
-		writeln!(w, "local memory_at_{mem}")?;
+	live_set
+}
+
+// Names may contain anything, so pick a long bracket level that they cannot close.
+fn write_func_name(name: &str, w: &mut dyn Write) -> Result<()> {
+	let mut level = String::new();
+
+	while name.contains(&format!("]{level}]")) {
+		level.push('=');
 	}
 
-	Ok(mem_set)
+	write!(w, "--[{level}[ {name} ]{level}] ")
 }
 
-fn write_func_start(wasm: &Module, index: u32, w: &mut dyn Write) -> Result<()> {
-	write!(w, "FUNC_LIST[{index}] = ")?;
+fn write_func_start(wasm: &Module, prefix: &str, index: u32, w: &mut dyn Write) -> Result<()> {
+	write!(w, "{prefix}FUNC_LIST[{index}] = ")?;
 
 	wasm.name_section()
 		.get(&index)
-		.map_or_else(|| Ok(()), |name| write!(w, "--[[ {name} ]] "))
+		.map_or_else(|| Ok(()), |name| write_func_name(name, w))
 }
 
-fn write_func_list(wasm: &Module, func_list: &[FuncData], w: &mut dyn Write) -> Result<()> {
-	let offset = wasm.import_count(External::Func);
+fn write_func_type(
+	type_info: &TypeInfo,
+	prefix: &str,
+	index: usize,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let id = type_info.canonical_type_index(type_info.func_type_index(index));
 
-	func_list.iter().enumerate().try_for_each(|(i, v)| {
-		let index = (offset + i).try_into().unwrap();
+	writeln!(w, "{prefix}FUNC_TYPE[{prefix}FUNC_LIST[{index}]] = {id}")
+}
 
-		write_func_start(wasm, index, w)?;
+fn write_func_list(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<(usize, usize)>> {
+	let prefix = &options.symbol_prefix;
+	let type_id_list: Vec<_> = (0..wasm.type_section().len())
+		.map(|i| type_info.canonical_type_index(i))
+		.collect();
+
+	let live_set = match options.strip_unused {
+		true => Some(find_live_set(wasm, &find_call_list(wasm)?)),
+		false => None,
+	};
+	let is_live = |index| live_set.as_ref().is_none_or(|set| set.contains(&index));
 
-		v.write(&mut Manager::function(v), w)
-	})
+	let mut size_list = Vec::new();
+
+	for_each_func(wasm, type_info, |i, v| {
+		if !is_live(i) {
+			return Ok(());
+		}
+
+		let index = i.try_into().unwrap();
+
+		let mut mng = Manager::function(v, options);
+		let mut w = CountWriter::new(w);
+
+		mng.set_function_index(i);
+		mng.set_num_memory_import(wasm.import_count(External::Memory));
+		mng.set_type_id_list(&type_id_list);
+		write_func_start(wasm, prefix, index, &mut w)?;
+		v.write(&mut mng, &mut w)?;
+
+		if options.check_signature {
+			write_func_type(type_info, prefix, i, &mut w)?;
+		}
+
+		size_list.push((i, w.count()));
+
+		Ok(())
+	})?;
+
+	Ok(size_list)
+}
+
+fn write_debug_list(prefix: &str, w: &mut dyn Write) -> Result<()> {
+	writeln!(w, "\t\tdebug = {{")?;
+	writeln!(w, "\t\t\ttable_list = {prefix}TABLE_LIST,")?;
+	writeln!(w, "\t\t}},")
+}
+
+fn write_dispose(
+	wasm: &Module,
+	mem_set: &BTreeSet<usize>,
+	prefix: &str,
+	w: &mut dyn Write,
+) -> Result<()> {
+	writeln!(w, "\t\tdispose = function()")?;
+
+	for mem in mem_set {
+		writeln!(w, "\t\t\t{prefix}memory_at_{mem} = nil")?;
+	}
+
+	let list = [
+		("TABLE_LIST", wasm.table_space()),
+		("MEMORY_LIST", wasm.memory_space()),
+		("DATA_LIST", wasm.data_section().len()),
+		("ELEM_LIST", wasm.element_section().len()),
+	];
+
+	for (name, _) in list.into_iter().filter(|v| v.1 != 0) {
+		writeln!(w, "\t\t\ttable.clear({prefix}{name})")?;
+	}
+
+	writeln!(w, "\t\tend,")
+}
+
+fn write_init_code(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = &options.symbol_prefix;
+
+	writeln!(w, "local function {prefix}run_init_code()")?;
+	write_table_list(wasm, prefix, w)?;
+	write_memory_list(wasm, prefix, w)?;
+	write_global_list(wasm, type_info, options, w)?;
+	write_element_list(wasm.element_section(), type_info, options, w)?;
+	write_data_list(wasm.data_section(), type_info, options, w)?;
+	writeln!(w, "end")
+}
+
+// A module that imports nothing and exports a single function needs no instance,
+// so that function can be handed back on its own.
+fn find_bare_export(wasm: &Module) -> Option<u32> {
+	let [export] = wasm.export_section() else {
+		return None;
+	};
+
+	let is_bare = External::from(export.kind) == External::Func && wasm.import_section().is_empty();
+
+	is_bare.then_some(export.index)
+}
+
+fn write_bare_start(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	mem_set: &BTreeSet<usize>,
+	options: &Options,
+	index: u32,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let prefix = &options.symbol_prefix;
+
+	writeln!(w, "{prefix}run_init_code()")?;
+
+	for mem in mem_set {
+		writeln!(w, "{prefix}memory_at_{mem} = {prefix}MEMORY_LIST[{mem}]")?;
+	}
+
+	if let Some(start) = wasm.start_section() {
+		writeln!(w, "{prefix}FUNC_LIST[{start}]()")?;
+	}
+
+	write!(w, "return ")?;
+	write_func_export(wasm, type_info, options, index, w)?;
+	writeln!(w)
 }
 
 fn write_module_start(
 	wasm: &Module,
 	type_info: &TypeInfo,
 	mem_set: &BTreeSet<usize>,
+	options: &Options,
 	w: &mut dyn Write,
 ) -> Result<()> {
-	writeln!(w, "local function run_init_code()")?;
-	write_table_list(wasm, w)?;
-	write_memory_list(wasm, w)?;
-	write_global_list(wasm, type_info, w)?;
-	write_element_list(wasm.element_section(), type_info, w)?;
-	write_data_list(wasm.data_section(), type_info, w)?;
-	writeln!(w, "end")?;
+	let prefix = &options.symbol_prefix;
 
 	writeln!(w, "return function(wasm)")?;
-	write_import_list(wasm.import_section(), w)?;
-	writeln!(w, "\trun_init_code()")?;
+	write_import_list(wasm.import_section(), prefix, w)?;
+
+	if options.check_signature {
+		for i in 0..wasm.import_count(External::Func) {
+			write!(w, "\t")?;
+			write_func_type(type_info, prefix, i, w)?;
+		}
+	}
+
+	writeln!(w, "\t{prefix}run_init_code()")?;
 
 	for mem in mem_set {
-		writeln!(w, "\tmemory_at_{mem} = MEMORY_LIST[{mem}]")?;
+		writeln!(w, "\t{prefix}memory_at_{mem} = {prefix}MEMORY_LIST[{mem}]")?;
 	}
 
-	if let Some(start) = wasm.start_section() {
-		writeln!(w, "\tFUNC_LIST[{start}]()")?;
+	let start = wasm.start_section();
+
+	if let Some(start) = start.filter(|_| !options.defer_start) {
+		writeln!(w, "\t{prefix}FUNC_LIST[{start}]()")?;
 	}
 
 	writeln!(w, "\treturn {{")?;
-	write_export_list(wasm.export_section(), w)?;
+	write_export_list(wasm, type_info, options, w)?;
+
+	if let Some(start) = start.filter(|_| options.defer_start) {
+		writeln!(w, "\t\t__start = {prefix}FUNC_LIST[{start}],")?;
+	}
+
+	if options.expose_tables {
+		write_debug_list(prefix, w)?;
+	}
+
+	if options.dispose {
+		write_dispose(wasm, mem_set, prefix, w)?;
+	}
+
+	if options.func_ids {
+		writeln!(w, "\t\tfunc_ids = {prefix}FUNC_IDS,")?;
+	}
+
 	writeln!(w, "\t}}")?;
 	writeln!(w, "end")
 }
 
+fn write_feature_check(name: &str, w: &mut dyn Write) -> Result<()> {
+	writeln!(w, r#"assert({name}, "requires Luau {name}")"#)
+}
+
+/// Writes the comment directives that must lead the output, before the runtime.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_inst_list(code: &[Operator], type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let ast = Factory::from_type_info(type_info).create_anonymous(code);
+pub fn write_header(options: &Options, w: &mut dyn Write) -> Result<()> {
+	if options.no_lint {
+		writeln!(w, "--!nolint")?;
+	}
 
-	ast.write(&mut Manager::function(&ast), w)
+	writeln!(w, "--!optimize 2")
 }
 
+// `continue` is syntax rather than a global, so it can only be checked where
+// the host lets code be compiled at runtime.
+fn write_continue_check(w: &mut dyn Write) -> Result<()> {
+	writeln!(
+		w,
+		r#"assert(not loadstring or loadstring("while false do continue end"), "requires Luau continue")"#
+	)
+}
+
+/// Writes assertions for the Luau features the runtime and the code generated
+/// with `options` rely on, so that a host lacking them fails early with a clear
+/// message.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_module_typed(wasm: &Module, type_info: &TypeInfo, w: &mut dyn Write) -> Result<()> {
-	let func_list = build_func_list(wasm, type_info);
-	let mem_set = write_localize_used(wasm, &func_list, w)?;
+pub fn write_prelude(options: &Options, w: &mut dyn Write) -> Result<()> {
+	write_feature_check("bit32", w)?;
+
+	if !options.host_memory {
+		write_feature_check("buffer", w)?;
+	}
+
+	write_feature_check("table.create", w)?;
+	write_feature_check("Vector3", w)?;
+
+	if !options.no_continue {
+		write_continue_check(w)?;
+	}
+
+	Ok(())
+}
 
-	write_named_array("FUNC_LIST", wasm.function_space(), w)?;
-	write_named_array("TABLE_LIST", wasm.table_space(), w)?;
-	write_named_array("MEMORY_LIST", wasm.memory_space(), w)?;
-	write_named_array("GLOBAL_LIST", wasm.global_space(), w)?;
+// Generated code only refers to the runtime's `rt` table of helpers and its
+// cached `bit32` and `math` functions.
+fn runtime_name_list() -> impl Iterator<Item = &'static str> {
+	crate::RUNTIME.lines().filter_map(|line| {
+		let line = line.strip_prefix("local ")?;
+		let line = line.strip_prefix("function ").unwrap_or(line);
+		let len = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
 
-	write_func_list(wasm, &func_list, w)?;
-	write_module_start(wasm, type_info, &mem_set, w)
+		Some(&line[..len])
+			.filter(|&name| name == "rt" || ["bit_", "math_"].iter().any(|v| name.starts_with(v)))
+	})
 }
 
+/// Writes the runtime as a standalone module returning its helpers by name, to be
+/// shared by every output translated with `Options::runtime_require`.
+///
 /// # Errors
 /// Returns `Err` if writing to `Write` failed.
-pub fn from_module_untyped(wasm: &Module, w: &mut dyn Write) -> Result<()> {
+pub fn write_runtime_module(w: &mut dyn Write) -> Result<()> {
+	writeln!(w, "{}", crate::RUNTIME)?;
+	writeln!(w, "return {{")?;
+
+	for name in runtime_name_list() {
+		writeln!(w, "\t{name} = {name},")?;
+	}
+
+	writeln!(w, "}}")
+}
+
+fn write_runtime_require(path: &str, w: &mut dyn Write) -> Result<()> {
+	writeln!(w, "local runtime = require({path})")?;
+
+	for name in runtime_name_list() {
+		writeln!(w, "local {name} = runtime.{name}")?;
+	}
+
+	Ok(())
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed.
+pub fn from_inst_list(
+	code: &[Operator],
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	let ast = Factory::from_type_info(type_info).create_anonymous(code);
+
+	ast.write(&mut Manager::function(&ast, options), w)
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
+fn write_module(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Vec<(usize, usize)>> {
+	let mem_set = (0..wasm.memory_space()).collect();
+
+	if let Some(path) = &options.runtime_require {
+		write_runtime_require(path, w)?;
+	}
+
+	let prefix = &options.symbol_prefix;
+	write_localize_memory(&mem_set, prefix, w)?;
+
+	write_named_array(prefix, "FUNC_LIST", wasm.function_space(), w)?;
+	write_named_array(prefix, "TABLE_LIST", wasm.table_space(), w)?;
+	write_named_array(prefix, "MEMORY_LIST", wasm.memory_space(), w)?;
+	write_named_array(prefix, "GLOBAL_LIST", wasm.global_space(), w)?;
+	write_named_array(prefix, "DATA_LIST", wasm.data_section().len(), w)?;
+	write_named_array(prefix, "ELEM_LIST", wasm.element_section().len(), w)?;
+
+	if options.func_ids {
+		write_func_ids(wasm, prefix, w)?;
+	}
+
+	if options.check_signature {
+		writeln!(
+			w,
+			r#"local {prefix}FUNC_TYPE = setmetatable({{}}, {{ __mode = "k" }})"#
+		)?;
+	}
+
+	let size_list = write_func_list(wasm, type_info, options, w)?;
+
+	write_init_code(wasm, type_info, options, w)?;
+
+	match find_bare_export(wasm).filter(|_| options.bare_function) {
+		Some(index) => write_bare_start(wasm, type_info, &mem_set, options, index, w)?,
+		None => write_module_start(wasm, type_info, &mem_set, options, w)?,
+	}
+
+	Ok(size_list)
+}
+
+/// Finds the runtime helpers and memories used by the function at `index` in
+/// the function space, without translating it.
+///
+/// # Errors
+/// Returns `Err` if the index names an import or no function, or the function
+/// is malformed.
+pub fn function_dependencies(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	index: usize,
+) -> Result<Dependencies> {
+	let offset = wasm.import_count(External::Func);
+	let body = index
+		.checked_sub(offset)
+		.and_then(|i| wasm.code_section().get(i))
+		.ok_or_else(|| {
+			Error::new(
+				ErrorKind::InvalidInput,
+				format!("no body for function {index}"),
+			)
+		})?;
+
+	let data = Factory::from_type_info(type_info)
+		.create_indexed(index, body)
+		.map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+	let (runtime_set, memory_set) = localize::visit(&data);
+
+	Ok(Dependencies {
+		runtime_set,
+		memory_set,
+	})
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
+pub fn from_module_typed(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<()> {
+	write_module(wasm, type_info, options, w).map(drop)
+}
+
+// Imported memories come first in the index space, which hosts built before
+// multi-memory may not expect when a module also defines its own.
+fn find_warning_list(wasm: &Module) -> Vec<String> {
+	let imported = wasm.import_count(External::Memory);
+	let mut warning_list = Vec::new();
+
+	if imported != 0 && !wasm.memory_section().is_empty() {
+		warning_list.push(format!(
+			"module both imports and defines memories, defined ones start at index {imported}"
+		));
+	}
+
+	warning_list
+}
+
+/// Translates the module like [`from_module_typed`], also reporting how many
+/// bytes were written in total and for each function, along with any warnings.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
+pub fn from_module_with_stats(
+	wasm: &Module,
+	type_info: &TypeInfo,
+	options: &Options,
+	w: &mut dyn Write,
+) -> Result<Stats> {
+	let mut w = CountWriter::new(w);
+	let func_list = write_module(wasm, type_info, options, &mut w)?;
+
+	Ok(Stats {
+		total: w.count(),
+		func_list,
+		warning_list: find_warning_list(wasm),
+	})
+}
+
+/// Translates a module given as WebAssembly text or binary, returning the Luau
+/// source without the runtime.
+///
+/// # Errors
+/// Returns `Err` if the input does not parse or a function is malformed.
+#[cfg(feature = "wat")]
+pub fn from_wat(source: &[u8], options: &Options) -> Result<String> {
+	let bytes = wat::parse_bytes(source).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+	let wasm = Module::try_from_data(&bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+	let mut data = Vec::new();
+
+	from_module_untyped(&wasm, options, &mut data)?;
+
+	String::from_utf8(data).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
+pub fn from_module_untyped(wasm: &Module, options: &Options, w: &mut dyn Write) -> Result<()> {
 	let type_info = TypeInfo::from_module(wasm);
 
-	from_module_typed(wasm, &type_info, w)
+	from_module_typed(wasm, &type_info, options, w)
+}
+
+/// Translates the module into a self-contained chunk, writing the header and the
+/// runtime ahead of it. Any `Options::runtime_require` is ignored, so the output
+/// never calls `require`.
+///
+/// # Errors
+/// Returns `Err` if writing to `Write` failed or a function is malformed.
+pub fn from_module_bundled(wasm: &Module, options: &Options, w: &mut dyn Write) -> Result<()> {
+	let options = Options {
+		runtime_require: None,
+		..options.clone()
+	};
+
+	write_header(&options, w)?;
+	writeln!(w, "{}", crate::RUNTIME)?;
+	from_module_untyped(wasm, &options, w)
 }
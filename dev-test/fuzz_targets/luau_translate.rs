@@ -1,5 +1,6 @@
 #![no_main]
 
+use codegen_luau::Options;
 use wasm_ast::module::Module;
 use wasm_smith::Module as RngModule;
 
@@ -9,5 +10,5 @@ libfuzzer_sys::fuzz_target!(|module: RngModule| {
 
 	let sink = &mut std::io::sink();
 
-	codegen_luau::from_module_untyped(&wasm, sink).expect("Luau should succeed");
+	codegen_luau::from_module_untyped(&wasm, &Options::default(), sink).expect("Luau should succeed");
 });
@@ -0,0 +1,24 @@
+(module
+	(memory 1)
+	(data (i32.const 0) "\00\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\ff\fe\fd\fc\00\00\00\00")
+	(data (i32.const 64) "plain text stays readable")
+	(data $passive "\de\ad\be\ef\00\00\00\00\00\00\00\00\00\00\00\00\ca\fe")
+
+	(func (export "byte") (param i32) (result i32) (i32.load8_u (local.get 0)))
+	(func (export "init") (memory.init $passive (i32.const 128) (i32.const 0) (i32.const 18)))
+)
+
+(assert_return (invoke "byte" (i32.const 0)) (i32.const 0x00))
+(assert_return (invoke "byte" (i32.const 10)) (i32.const 0x0a))
+(assert_return (invoke "byte" (i32.const 16)) (i32.const 0xff))
+(assert_return (invoke "byte" (i32.const 19)) (i32.const 0xfc))
+(assert_return (invoke "byte" (i32.const 24)) (i32.const 0x00))
+(assert_return (invoke "byte" (i32.const 64)) (i32.const 0x70))
+(assert_return (invoke "byte" (i32.const 88)) (i32.const 0x65))
+
+(invoke "init")
+(assert_return (invoke "byte" (i32.const 128)) (i32.const 0xde))
+(assert_return (invoke "byte" (i32.const 131)) (i32.const 0xef))
+(assert_return (invoke "byte" (i32.const 144)) (i32.const 0xca))
+(assert_return (invoke "byte" (i32.const 145)) (i32.const 0xfe))
+(assert_return (invoke "byte" (i32.const 146)) (i32.const 0x00))
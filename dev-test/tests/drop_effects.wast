@@ -0,0 +1,37 @@
+(module
+	(memory 1)
+	(global $count (mut i32) (i32.const 0))
+
+	(func $bump (result i32)
+		(global.set $count (i32.add (global.get $count) (i32.const 1)))
+		(global.get $count)
+	)
+
+	(func (export "drop_call") (result i32)
+		(drop (call $bump))
+		(drop (call $bump))
+		(global.get $count)
+	)
+
+	(func (export "drop_load") (param i32)
+		(drop (i32.load (local.get 0)))
+	)
+
+	(func (export "drop_div") (param i32)
+		(drop (i32.div_u (i32.const 1) (local.get 0)))
+	)
+
+	(func (export "drop_pure") (param i32) (result i32)
+		(nop)
+		(drop (i32.add (local.get 0) (i32.const 1)))
+		(nop)
+		(local.get 0)
+	)
+)
+
+(assert_return (invoke "drop_call") (i32.const 2))
+(assert_return (invoke "drop_load" (i32.const 0)))
+(assert_trap (invoke "drop_load" (i32.const 65536)) "out of bounds memory access")
+(assert_return (invoke "drop_div" (i32.const 1)))
+(assert_trap (invoke "drop_div" (i32.const 0)) "integer divide by zero")
+(assert_return (invoke "drop_pure" (i32.const 7)) (i32.const 7))
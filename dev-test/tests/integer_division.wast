@@ -0,0 +1,35 @@
+(module
+	(func (export "div_s_i32") (param i32 i32) (result i32) (i32.div_s (local.get 0) (local.get 1)))
+	(func (export "div_u_i32") (param i32 i32) (result i32) (i32.div_u (local.get 0) (local.get 1)))
+	(func (export "rem_s_i32") (param i32 i32) (result i32) (i32.rem_s (local.get 0) (local.get 1)))
+	(func (export "rem_u_i32") (param i32 i32) (result i32) (i32.rem_u (local.get 0) (local.get 1)))
+
+	(func (export "div_s_i64") (param i64 i64) (result i64) (i64.div_s (local.get 0) (local.get 1)))
+	(func (export "div_u_i64") (param i64 i64) (result i64) (i64.div_u (local.get 0) (local.get 1)))
+	(func (export "rem_s_i64") (param i64 i64) (result i64) (i64.rem_s (local.get 0) (local.get 1)))
+	(func (export "rem_u_i64") (param i64 i64) (result i64) (i64.rem_u (local.get 0) (local.get 1)))
+)
+
+(assert_trap (invoke "div_s_i32" (i32.const 5) (i32.const 0)) "integer divide by zero")
+(assert_trap (invoke "div_u_i32" (i32.const 5) (i32.const 0)) "integer divide by zero")
+(assert_trap (invoke "rem_s_i32" (i32.const 5) (i32.const 0)) "integer divide by zero")
+(assert_trap (invoke "rem_u_i32" (i32.const 5) (i32.const 0)) "integer divide by zero")
+(assert_trap (invoke "div_s_i64" (i64.const 5) (i64.const 0)) "integer divide by zero")
+(assert_trap (invoke "div_u_i64" (i64.const 5) (i64.const 0)) "integer divide by zero")
+(assert_trap (invoke "rem_s_i64" (i64.const 5) (i64.const 0)) "integer divide by zero")
+(assert_trap (invoke "rem_u_i64" (i64.const 5) (i64.const 0)) "integer divide by zero")
+
+(assert_trap (invoke "div_s_i32" (i32.const 0x80000000) (i32.const -1)) "integer overflow")
+(assert_trap (invoke "div_s_i64" (i64.const 0x8000000000000000) (i64.const -1)) "integer overflow")
+(assert_return (invoke "rem_s_i32" (i32.const 0x80000000) (i32.const -1)) (i32.const 0))
+(assert_return (invoke "rem_s_i64" (i64.const 0x8000000000000000) (i64.const -1)) (i64.const 0))
+
+(assert_return (invoke "div_s_i32" (i32.const -7) (i32.const 2)) (i32.const -3))
+(assert_return (invoke "rem_s_i32" (i32.const -7) (i32.const 2)) (i32.const -1))
+(assert_return (invoke "div_s_i64" (i64.const -7) (i64.const 2)) (i64.const -3))
+(assert_return (invoke "rem_s_i64" (i64.const -7) (i64.const 2)) (i64.const -1))
+
+(assert_return (invoke "div_u_i32" (i32.const -1) (i32.const 2)) (i32.const 0x7FFFFFFF))
+(assert_return (invoke "rem_u_i32" (i32.const -1) (i32.const 10)) (i32.const 5))
+(assert_return (invoke "div_u_i64" (i64.const -1) (i64.const 2)) (i64.const 0x7FFFFFFFFFFFFFFF))
+(assert_return (invoke "rem_u_i64" (i64.const -1) (i64.const 10)) (i64.const 5))
@@ -0,0 +1,24 @@
+(module
+	(memory 1)
+
+	(func (export "store_i32") (param i32) (i32.store (i32.const 0) (local.get 0)))
+	(func (export "store_i64") (param i64) (i64.store (i32.const 8) (local.get 0)))
+	(func (export "store_f32") (param f32) (f32.store (i32.const 16) (local.get 0)))
+	(func (export "byte") (param i32) (result i32) (i32.load8_u (local.get 0)))
+)
+
+(invoke "store_i32" (i32.const 0x01020304))
+(assert_return (invoke "byte" (i32.const 0)) (i32.const 0x04))
+(assert_return (invoke "byte" (i32.const 1)) (i32.const 0x03))
+(assert_return (invoke "byte" (i32.const 2)) (i32.const 0x02))
+(assert_return (invoke "byte" (i32.const 3)) (i32.const 0x01))
+
+(invoke "store_i64" (i64.const 0x0102030405060708))
+(assert_return (invoke "byte" (i32.const 8)) (i32.const 0x08))
+(assert_return (invoke "byte" (i32.const 11)) (i32.const 0x05))
+(assert_return (invoke "byte" (i32.const 15)) (i32.const 0x01))
+
+(invoke "store_f32" (f32.const 1))
+(assert_return (invoke "byte" (i32.const 16)) (i32.const 0x00))
+(assert_return (invoke "byte" (i32.const 18)) (i32.const 0x80))
+(assert_return (invoke "byte" (i32.const 19)) (i32.const 0x3f))
@@ -0,0 +1,225 @@
+use std::io::Result;
+
+use wasm_ast::module::{Module, TypeInfo};
+use wast::{parser::ParseBuffer, Wat};
+
+fn translate(source: &str) -> Result<String> {
+	let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
+	let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+	let bytes = parsed.encode().unwrap();
+
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let mut data = Vec::new();
+
+	codegen_luajit::from_module_untyped(&wasm, &mut data)?;
+
+	Ok(String::from_utf8(data).unwrap())
+}
+
+#[test]
+fn store_past_memory_size_traps() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func (param i32) (i32.store offset=65536 (local.get 0) (i32.const 1)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+	let runtime = codegen_luajit::RUNTIME;
+
+	assert!(data.contains("store_i32(memory_at_0, loc_0 + 65536, 1)"));
+	assert!(runtime
+		.contains("function store.i32(memory, addr, value)\n\t\tby_checked(memory, addr, 4)"));
+	assert!(runtime.contains("if addr < 0 or addr + size > memory.min * WASM_PAGE_SIZE then"));
+	assert!(runtime.contains(r#"trap_raise("out of bounds memory access", size .. " bytes at ""#));
+}
+
+#[test]
+fn start_resolves_through_imports() {
+	let source = r#"
+		(module
+			(import "env" "init" (func $init))
+			(func $own)
+			(start $init)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"FUNC_LIST[0] = wasm["env"].func_list["init"]"#));
+	assert!(data.contains("\tFUNC_LIST[0]()\n"));
+}
+
+#[test]
+fn unreachable_routes_to_trap() {
+	let source = r#"
+		(module
+			(func unreachable)
+			(func unreachable)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"rt.trap.raise("unreachable", "out of code bounds in function 0")"#));
+	assert!(data.contains(r#"rt.trap.raise("unreachable", "out of code bounds in function 1")"#));
+	assert!(codegen_luajit::RUNTIME.contains("function trap.set_handler(value)"));
+}
+
+// The swapped path only runs on big-endian hosts, so this checks that every
+// multi-byte access has a swapped counterpart and that both helpers reverse.
+#[test]
+fn big_endian_swaps_every_wide_access() {
+	let runtime = codegen_luajit::RUNTIME;
+	let start = runtime.find("local function by_checked(").unwrap();
+	let split = runtime.find("if ffi.abi(\"be\") then").unwrap();
+	let end = split + runtime[split..].find("\n\tend\n").unwrap();
+
+	let native = &runtime[start..split];
+	let swapped = &runtime[split..end];
+	let mut name = "";
+	let mut count = 0;
+
+	for line in native.lines() {
+		if let Some(rest) = line.trim_start().strip_prefix("function ") {
+			name = rest.split_once('(').unwrap().0;
+		} else if [2, 4, 8]
+			.iter()
+			.any(|size| line.contains(&format!("by_checked(memory, addr, {size})")))
+		{
+			assert!(swapped.contains(&format!("function {name}(")), "{name}");
+
+			count += 1;
+		}
+	}
+
+	assert_eq!(count, 17);
+	assert!(swapped.contains("bytes[i] = source[size - 1 - i]"));
+	assert!(swapped.contains("target[i] = bytes[size - 1 - i]"));
+}
+
+#[test]
+fn element_segment_checks_table_bounds() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func $f)
+			(elem (i32.const 0) $f $f)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("\t\tif offset < 0 or offset + 2 > target.min then\n"));
+	assert!(data.contains(
+		r#"rt.trap.raise("out of bounds table access", "element segment 0 does not fit")"#
+	));
+}
+
+#[test]
+fn signed_division_goes_through_runtime() {
+	let source = r#"
+		(module
+			(func (param i32 i32) (result i32) (i32.rem_s (local.get 0) (local.get 1)))
+			(func (param i64 i64) (result i64) (i64.div_s (local.get 0) (local.get 1)))
+			(func (param i64 i64) (result i64) (i64.rem_s (local.get 0) (local.get 1)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("local rem_i32 = rt.rem.i32\n"));
+	assert!(data.contains("div_i64(loc_0, loc_1)"));
+	assert!(data.contains("rem_i64(loc_0, loc_1)"));
+	assert!(!data.contains("math.fmod"));
+}
+
+#[test]
+fn dependencies_of_add_only_function() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func (param i32 i32) (result i32)
+				(i32.add (local.get 0) (local.get 1))
+			)
+			(func (param i32) (result i32)
+				(i32.load (local.get 0))
+			)
+		)
+	"#;
+
+	let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
+	let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+	let bytes = parsed.encode().unwrap();
+
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let type_info = TypeInfo::from_module(&wasm);
+
+	let add = codegen_luajit::function_dependencies(&wasm, &type_info, 0).unwrap();
+	let load = codegen_luajit::function_dependencies(&wasm, &type_info, 1).unwrap();
+
+	assert_eq!(
+		add.runtime_set().iter().collect::<Vec<_>>(),
+		[&("add", "i32")]
+	);
+	assert!(add.memory_set().is_empty());
+	assert_eq!(
+		load.runtime_set().iter().collect::<Vec<_>>(),
+		[&("load", "i32")]
+	);
+	assert_eq!(load.memory_set().iter().collect::<Vec<_>>(), [&0]);
+}
+
+#[test]
+fn trimmed_runtime_keeps_only_used_operations() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func (param i32 i32) (result i32)
+				(i32.load (i32.clz (i32.div_s (i32.add (local.get 0) (local.get 1)) (local.get 1))))
+			)
+		)
+	"#;
+
+	let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
+	let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+	let bytes = parsed.encode().unwrap();
+
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let type_info = TypeInfo::from_module(&wasm);
+	let mut data = Vec::new();
+
+	codegen_luajit::write_runtime_trimmed(&wasm, &type_info, &mut data).unwrap();
+
+	let runtime = String::from_utf8(data).unwrap();
+
+	assert!(runtime.contains("function add.i32(lhs, rhs)"));
+	assert!(runtime.contains("function div.i32(lhs, rhs)"));
+	assert!(runtime.contains("function clz.i32(num)"));
+	assert!(runtime.contains("function load.i32(memory, addr)"));
+	assert!(runtime.contains("function allocator.new(min, max)"));
+	assert!(!runtime.contains("function add.i64("));
+	assert!(!runtime.contains(".f32"));
+	assert!(!runtime.contains(".f64"));
+}
+
+#[test]
+fn shifts_use_bit_library() {
+	let source = r#"
+		(module
+			(func (param i32 i32) (result i32) (i32.shl (local.get 0) (local.get 1)))
+			(func (param i32 i32) (result i32) (i32.shr_u (local.get 0) (local.get 1)))
+			(func (param i32 i32) (result i32) (i32.rotl (local.get 0) (local.get 1)))
+			(func (param i32 i32) (result i32) (i32.lt_u (local.get 0) (local.get 1)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("local shl_i32 = bit.lshift"));
+	assert!(data.contains("local shr_u32 = bit.rshift"));
+	assert!(data.contains("local rotl_i32 = bit.rol"));
+	assert!(data.contains("(lt_u32(loc_0, loc_1) and 1 or 0)"));
+	assert!(!data.contains("<<") && !data.contains(">>"));
+}
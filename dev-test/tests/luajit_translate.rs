@@ -231,3 +231,86 @@ fn translate_file(path: PathBuf) {
 
 	LuaJIT::test(name, &source).unwrap();
 }
+
+#[test]
+fn mixed_memory() {
+	let source = include_str!("mixed_memory.wast");
+
+	LuaJIT::test("mixed_memory.wast", source).unwrap();
+}
+
+#[test]
+fn memory_grow_failure() {
+	let source = include_str!("memory_grow_failure.wast");
+
+	LuaJIT::test("memory_grow_failure.wast", source).unwrap();
+}
+
+#[test]
+fn unaligned_access() {
+	let source = include_str!("unaligned_access.wast");
+
+	LuaJIT::test("unaligned_access.wast", source).unwrap();
+}
+
+#[test]
+fn little_endian() {
+	let source = include_str!("little_endian.wast");
+
+	LuaJIT::test("little_endian.wast", source).unwrap();
+}
+
+#[test]
+fn integer_division() {
+	let source = include_str!("integer_division.wast");
+
+	LuaJIT::test("integer_division.wast", source).unwrap();
+}
+
+#[test]
+fn unsigned_shift() {
+	let source = include_str!("unsigned_shift.wast");
+
+	LuaJIT::test("unsigned_shift.wast", source).unwrap();
+}
+
+#[test]
+fn drop_effects() {
+	let source = include_str!("drop_effects.wast");
+
+	LuaJIT::test("drop_effects.wast", source).unwrap();
+}
+
+#[test]
+fn memory_size() {
+	let source = include_str!("memory_size.wast");
+	let epilogue = r#"
+local memory = rt.allocator.new(2, 4)
+
+assert(memory.min == 2 and rt.allocator.byte_size(memory) == 131072)
+assert(rt.allocator.grow(memory, 1) == 2)
+assert(memory.min == 3 and rt.allocator.byte_size(memory) == 196608)
+"#;
+
+	LuaJIT::test_with_epilogue("memory_size.wast", source, epilogue).unwrap();
+}
+
+#[test]
+fn trap_kinds() {
+	let source = include_str!("trap_kinds.wast");
+	let epilogue = r#"
+for _, case in ipairs({
+	{ "unreachable", "unreachable" },
+	{ "integer divide by zero", "divide", 0 },
+	{ "integer overflow", "overflow", -1 },
+	{ "out of bounds memory access", "load", 65536 },
+	{ "out of bounds table access", "table", 2 },
+}) do
+	local success, message = pcall(loaded["temp"].func_list[case[2]], case[3])
+
+	assert(not success and rt.trap.kind(message) == case[1], case[2] .. " did not raise " .. case[1])
+end
+"#;
+
+	LuaJIT::test_with_epilogue("trap_kinds.wast", source, epilogue).unwrap();
+}
@@ -2,13 +2,13 @@ do
 	local WASM_PAGE_SIZE = 65536
 
 	local function is_valid_address(memory, addr, size)
-		return addr >= 0 and addr + size <= rt.allocator.size(memory) * WASM_PAGE_SIZE
+		return addr >= 0 and addr + size <= rt.allocator_size(memory) * WASM_PAGE_SIZE
 	end
 
 	local function load_checked(name, size)
-		local old = assert(rt.load[name], "Missing load function " .. name)
+		local old = assert(rt["load_" .. name], "Missing load function " .. name)
 
-		rt.load[name] = function(memory, addr)
+		rt["load_" .. name] = function(memory, addr)
 			assert(is_valid_address(memory, addr, size), "Invalid memory read")
 
 			return old(memory, addr)
@@ -16,9 +16,9 @@ do
 	end
 
 	local function store_checked(name, size)
-		local old = assert(rt.store[name], "Missing store function " .. name)
+		local old = assert(rt["store_" .. name], "Missing store function " .. name)
 
-		rt.store[name] = function(memory, addr, value)
+		rt["store_" .. name] = function(memory, addr, value)
 			assert(is_valid_address(memory, addr, size), "Invalid memory write")
 
 			return old(memory, addr, value)
@@ -26,9 +26,9 @@ do
 	end
 
 	do
-		local old = rt.store.string
+		local old = rt.store_string
 
-		function rt.store.string(memory, addr, data, len)
+		function rt.store_string(memory, addr, data, len)
 			assert(is_valid_address(memory, addr, #data), "Invalid memory write")
 
 			return old(memory, addr, data, len)
@@ -71,7 +71,7 @@ local LUA_INFINITY = math.huge
 
 local function is_number_equal(lhs, rhs)
 	if type(lhs) == "table" and type(rhs) == "table" then
-		return rt.eq.i64(lhs, rhs)
+		return rt.eq_i64(lhs, rhs)
 	elseif type(lhs) ~= "number" or type(rhs) ~= "number" then
 		return false
 	elseif lhs ~= lhs and rhs ~= rhs then
@@ -83,7 +83,7 @@ end
 
 local function to_string(data)
 	if type(data) == "table" then
-		data = rt.convert.f64_i64(data)
+		data = rt.convert_f64_i64(data)
 	end
 
 	return tostring(data)
@@ -150,8 +150,8 @@ linked.spectest = {
 		global_f32 = { value = 666 },
 		global_f64 = { value = 666 },
 		global_i32 = { value = 666 },
-		global_i64 = { value = rt.i64.from_u32(666, 0) },
+		global_i64 = { value = rt.i64_from_u32(666, 0) },
 	},
 	table_list = { table = { data = {} } },
-	memory_list = { memory = rt.allocator.new(1, 2) },
+	memory_list = { memory = rt.allocator_new(1, 2) },
 }
@@ -0,0 +1,2149 @@
+use std::{io::Result, rc::Rc};
+
+use codegen_luau::{Hook, I64Export, Options};
+use wasm_ast::module::{External, Module, TypeInfo};
+use wast::{parser::ParseBuffer, Wat};
+
+fn encode(source: &str) -> Vec<u8> {
+	let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
+	let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+
+	parsed.encode().unwrap()
+}
+
+fn translate_with(source: &str, options: &Options) -> Result<String> {
+	let bytes = encode(source);
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let mut data = Vec::new();
+
+	codegen_luau::from_module_untyped(&wasm, options, &mut data)?;
+
+	Ok(String::from_utf8(data).unwrap())
+}
+
+fn translate(source: &str) -> Result<String> {
+	translate_with(source, &Options::default())
+}
+
+// The instance exposes the runtime helpers by name, so checks for what a function
+// calls have to look at its body alone.
+fn function_body(data: &str, index: usize) -> &str {
+	let start = data.find(&format!("FUNC_LIST[{index}] = ")).unwrap();
+	let end = data[start..].find("\nend").unwrap();
+
+	&data[start..start + end]
+}
+
+#[test]
+fn return_arity_mismatch() {
+	let source = r#"
+		(module
+			(func (result i32 i32)
+				i32.const 1
+				return
+			)
+		)
+	"#;
+
+	assert!(translate(source).is_err());
+}
+
+fn prelude(options: &Options) -> String {
+	let mut data = Vec::new();
+
+	codegen_luau::write_prelude(options, &mut data).unwrap();
+
+	String::from_utf8(data).unwrap()
+}
+
+#[test]
+fn prelude_checks_buffer() {
+	let prelude = prelude(&Options::default());
+
+	assert!(prelude.contains(r#"assert(buffer, "requires Luau buffer")"#));
+	assert!(prelude.contains(r#""requires Luau continue")"#));
+}
+
+#[test]
+fn prelude_skips_unneeded_checks() {
+	let options = Options {
+		host_memory: true,
+		no_continue: true,
+		..Options::default()
+	};
+
+	let prelude = prelude(&options);
+
+	assert!(!prelude.contains("buffer"));
+	assert!(!prelude.contains("continue"));
+	assert!(prelude.contains(r#"assert(bit32, "requires Luau bit32")"#));
+}
+
+#[test]
+fn debug_call_arity() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func $callee (param i32 i32))
+			(func
+				i32.const 1
+				i32.const 2
+				call $callee
+				i32.const 1
+				i32.const 2
+				i32.const 0
+				call_indirect (param i32 i32)
+			)
+		)
+	"#;
+
+	let options = Options {
+		debug: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+	let body = function_body(&data, 1);
+
+	assert_eq!(body.matches("rt.assert_arity(").count(), 1);
+	assert!(body.contains("rt.assert_arity(TABLE_LIST[0].data[index], 2)"));
+}
+
+#[test]
+fn br_table_sparse() {
+	let labels: Vec<_> = (0..256).map(|i| if i == 5 { "0" } else { "1" }).collect();
+	let source = format!(
+		r#"
+		(module
+			(func (param i32)
+				(block
+					(block
+						(br_table {} 1 (local.get 0))
+					)
+				)
+			)
+		)
+	"#,
+		labels.join(" ")
+	);
+
+	let data = translate(&source).unwrap();
+
+	assert!(data.contains("[1] = { [5] = 0, },"));
+}
+
+#[test]
+fn equal_zero_direct() {
+	let source = r#"
+		(module
+			(func (param i32 i64) (result i32 i32)
+				(i32.eqz (local.get 0))
+				(i64.eqz (local.get 1))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("(if loc_0 == 0 then 1 else 0)"));
+	assert!(data.contains("(if loc_1 == rt.i64_ZERO then 1 else 0)"));
+}
+
+#[test]
+fn unreachable_routes_to_trap() {
+	let source = r#"
+		(module
+			(func
+				unreachable
+			)
+			(func
+				unreachable
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"rt.trap("unreachable", "out of code bounds in function 0")"#));
+	assert!(data.contains(r#"rt.trap("unreachable", "out of code bounds in function 1")"#));
+	assert!(!data.contains("error("));
+	assert!(codegen_luau::EXPORT_RUNTIME.contains("set_trap_handler = rt.set_trap_handler"));
+}
+
+#[test]
+fn float_constant_round_trip() {
+	let list_f32 = [
+		("inexact decimal", 0.1),
+		("smallest denormal", f32::from_bits(1)),
+		("largest denormal", f32::from_bits(0x007F_FFFF)),
+		("smallest normal", -f32::MIN_POSITIVE),
+		("largest finite", f32::MAX),
+		("last exact integer", 16_777_216.0),
+	];
+	let list_f64 = [
+		("inexact decimal", 0.1),
+		("smallest denormal", f64::from_bits(1)),
+		("smallest normal", f64::MIN_POSITIVE),
+		("largest finite", f64::MAX),
+	];
+
+	let globals: String = list_f32
+		.iter()
+		.map(|v| format!("(global f32 (f32.const {:e}))", v.1))
+		.chain(
+			list_f64
+				.iter()
+				.map(|v| format!("(global f64 (f64.const {:e}))", v.1)),
+		)
+		.collect();
+
+	let data = translate(&format!("(module {globals})")).unwrap();
+	let parsed: Vec<f64> = data
+		.lines()
+		.filter_map(|v| v.trim().strip_prefix("GLOBAL_LIST["))
+		.map(|v| {
+			let start = v.find("value = ").unwrap() + 8;
+			let end = v.rfind(" }").unwrap();
+
+			v[start..end].parse().unwrap()
+		})
+		.collect();
+
+	for (i, &(name, v)) in list_f32.iter().enumerate() {
+		#[allow(clippy::cast_possible_truncation)]
+		let number = parsed[i] as f32;
+
+		assert_eq!(number.to_bits(), v.to_bits(), "f32 {name}");
+		assert_eq!(parsed[i].to_bits(), f64::from(v).to_bits(), "f32 {name}");
+	}
+
+	for (i, &(name, v)) in list_f64.iter().enumerate() {
+		assert_eq!(
+			parsed[list_f32.len() + i].to_bits(),
+			v.to_bits(),
+			"f64 {name}"
+		);
+	}
+}
+
+#[test]
+fn strict_f32_demotes_chain() {
+	let source = r#"
+		(module
+			(func (param f32 f32 f32) (result f32)
+				(f32.add (f32.add (local.get 0) (local.get 1)) (local.get 2))
+			)
+		)
+	"#;
+
+	let options = Options {
+		strict_f32: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("rt.demote_f32_f64((rt.demote_f32_f64((loc_0 + loc_1)) + loc_2))"));
+	assert!(!function_body(&translate(source).unwrap(), 0).contains("rt.demote_f32_f64"));
+}
+
+#[test]
+fn i64_constant_split_words() {
+	let source = r#"
+		(module
+			(func (result i64)
+				i64.const 0x7FFFFFFFFFFFFFFF
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.i64_from_u32(4294967295, 2147483647)"));
+	assert!(!data.contains("9223372036854775807"));
+}
+
+#[test]
+fn expose_tables_accessor() {
+	let source = r#"
+		(module
+			(table 2 funcref)
+			(func $first)
+			(func $second)
+			(elem (i32.const 1) $second)
+		)
+	"#;
+
+	let options = Options {
+		expose_tables: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("local data = { FUNC_LIST[1],"));
+	assert!(data.contains("table_list = TABLE_LIST,"));
+	assert!(!translate(source)
+		.unwrap()
+		.contains("table_list = TABLE_LIST,"));
+}
+
+#[test]
+fn function_name_comment_escaped() {
+	// A hand written name section, as the text format cannot spell this name.
+	let source = r#"
+		(module
+			(func)
+			(@custom "name" "\01\14\01\00\11foo]]print(\"x\")--")
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"FUNC_LIST[0] = --[=[ foo]]print("x")-- ]=] "#));
+}
+
+#[test]
+fn float_division_by_zero_is_native() {
+	let source = r#"
+		(module
+			(func (result f64 f64)
+				(f64.div (f64.const 1) (f64.const 0))
+				(f64.div (f64.const -1) (f64.const 0))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	// Lua's `/` yields signed infinity here, unlike the trapping integer helpers.
+	assert!(data.contains("(1e0 / 0e0)"));
+	assert!(data.contains("(-1e0 / 0e0)"));
+	assert!(!function_body(&data, 0).contains("rt.div_"));
+}
+
+#[test]
+fn bulk_memory_calls() {
+	let source = r#"
+		(module
+			(memory 1)
+			(data "hello")
+			(func
+				(memory.copy (i32.const 0) (i32.const 8) (i32.const 4))
+				(memory.fill (i32.const 0) (i32.const 255) (i32.const 4))
+				(memory.init 0 (i32.const 16) (i32.const 1) (i32.const 3))
+				(data.drop 0)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"DATA_LIST[0] = "hello""#));
+	assert!(data.contains("rt.store_copy(memory_at_0, 0, memory_at_0, 8, 4)"));
+	assert!(data.contains("rt.store_fill(memory_at_0, 0, 4, 255)"));
+	assert!(data.contains("rt.store_init(memory_at_0, 16, DATA_LIST[0], 1, 3)"));
+	assert!(data.contains("DATA_LIST[0] = nil"));
+}
+
+#[test]
+fn i32_add_wraps_through_runtime() {
+	let source = r#"
+		(module
+			(func (param i32) (result i32)
+				(i32.add (local.get 0) (i32.const 1))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.add_i32(loc_0, 1)"));
+	assert!(codegen_luau::RUNTIME.contains("return bit_or(lhs + rhs, 0)"));
+}
+
+#[test]
+fn host_memory_accessors() {
+	let source = r#"
+		(module
+			(import "env" "memory" (memory 1))
+			(func (param i32) (result i32)
+				(i32.store (local.get 0) (i32.const 7))
+				(i32.load offset=4 (local.get 0))
+			)
+		)
+	"#;
+
+	let options = Options {
+		host_memory: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("memory_at_0.write_i32(loc_0, 7)"));
+	assert!(data.contains("memory_at_0.read_i32(loc_0 + 4)"));
+	assert!(!data.contains("rt.load_i32(memory_at_0"));
+}
+
+#[test]
+fn missing_code_section() {
+	// One `() -> ()` type and one function using it, but no code section.
+	let bytes = [
+		0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03,
+		0x02, 0x01, 0x00,
+	];
+
+	assert!(Module::try_from_data(&bytes).is_err());
+}
+
+#[test]
+fn import_only_module() {
+	let source = r#"
+		(module
+			(import "env" "func" (func))
+		)
+	"#;
+
+	assert!(translate(source).is_ok());
+}
+
+#[test]
+fn overlapping_data_in_order() {
+	let source = r#"
+		(module
+			(memory 1)
+			(data (i32.const 0) "aaaa")
+			(data (i32.const 2) "bb")
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+	let first = data.find(r#"MEMORY_LIST[0], 0,"aaaa")"#);
+	let second = data.find(r#"MEMORY_LIST[0], 2,"bb")"#);
+
+	// Later segments must be written last so their bytes win in the overlap.
+	assert!(first.unwrap() < second.unwrap());
+}
+
+#[test]
+fn br_table_hoisted_from_loop() {
+	let labels: Vec<_> = (0..256).map(|i| (i % 2).to_string()).collect();
+	let source = format!(
+		r#"
+		(module
+			(func (param i32)
+				(block
+					(loop
+						(br_table {} 1 (local.get 0))
+					)
+				)
+			)
+		)
+	"#,
+		labels.join(" ")
+	);
+
+	let data = translate(&source).unwrap();
+	let table = data.find("local br_map = {").unwrap();
+
+	assert_eq!(data.matches("[0] = 0, 1, 0, 1,").count(), 1);
+	assert!(table < data.find("while true do").unwrap());
+	assert!(!data.contains("if not br_map"));
+}
+
+#[test]
+fn segment_init_names_index() {
+	let source = r#"
+		(module
+			(memory 1)
+			(table 1 funcref)
+			(func)
+			(elem (i32.const 0) 0)
+			(data (i32.const 0) "a")
+			(data (i32.const 70000) "b")
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"rt.init_segment("element segment 0", function()"#));
+	assert!(data.contains(
+		r#"rt.init_segment("data segment 1", rt.store_string, MEMORY_LIST[0], 70000,"b")"#
+	));
+	assert!(codegen_luau::RUNTIME.contains(r#"error(name .. " failed: " .. tostring(message), 0)"#));
+}
+
+#[test]
+fn inner_branches_skip_desired() {
+	let source = r#"
+		(module
+			(func (param i32)
+				(loop
+					(br_if 0 (local.get 0))
+				)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("continue"));
+	assert!(!data.contains("desired ="));
+}
+
+#[test]
+fn overflowing_global_initializer() {
+	let source = r#"
+		(module
+			(global i32 (i32.add (i32.const 0x7FFFFFFF) (i32.const 1)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	// Folded with wrapping, giving -2147483648 as an i32.
+	assert!(data.contains("GLOBAL_LIST[0] = { value = 2147483648 }"));
+}
+
+#[test]
+fn deferred_start_function() {
+	let source = r#"
+		(module
+			(func $main)
+			(start $main)
+		)
+	"#;
+
+	let options = Options {
+		defer_start: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(!data.contains("\tFUNC_LIST[0]()"));
+	assert!(data.contains("__start = FUNC_LIST[0],"));
+	assert!(translate(source).unwrap().contains("\tFUNC_LIST[0]()"));
+}
+
+#[test]
+fn sign_extension_calls() {
+	let source = r#"
+		(module
+			(func (param i32 i64) (result i32 i32 i64 i64 i64)
+				(i32.extend8_s (local.get 0))
+				(i32.extend16_s (local.get 0))
+				(i64.extend8_s (local.get 1))
+				(i64.extend16_s (local.get 1))
+				(i64.extend32_s (local.get 1))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	for name in ["i32_n8", "i32_n16"] {
+		assert!(data.contains(&format!("rt.extend_{name}(loc_0)")));
+	}
+
+	for name in ["i64_n8", "i64_n16", "i64_n32"] {
+		assert!(data.contains(&format!("rt.extend_{name}(loc_1)")));
+	}
+}
+
+#[test]
+fn saturating_truncation_calls() {
+	let source = r#"
+		(module
+			(func (param f32 f64) (result i32 i32 i64 i64 i32 i32 i64 i64)
+				(i32.trunc_sat_f32_s (local.get 0))
+				(i32.trunc_sat_f32_u (local.get 0))
+				(i64.trunc_sat_f32_s (local.get 0))
+				(i64.trunc_sat_f32_u (local.get 0))
+				(i32.trunc_sat_f64_s (local.get 1))
+				(i32.trunc_sat_f64_u (local.get 1))
+				(i64.trunc_sat_f64_s (local.get 1))
+				(i64.trunc_sat_f64_u (local.get 1))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	// Every `f32` is exactly representable, so both share the `f64` helpers.
+	for name in ["i32", "u32", "i64", "u64"] {
+		assert!(data.contains(&format!("rt.saturate_{name}_f64(loc_0)")));
+		assert!(data.contains(&format!("rt.saturate_{name}_f64(loc_1)")));
+	}
+}
+
+#[test]
+fn export_order_is_stable() {
+	let first = r#"
+		(module
+			(func (export "b"))
+			(func (export "a"))
+		)
+	"#;
+
+	let second = r#"
+		(module
+			(func (export "a"))
+			(func (export "b"))
+		)
+	"#;
+
+	let first = translate(first).unwrap();
+	let second = translate(second).unwrap();
+
+	let first = &first[first.find("func_list = {").unwrap()..];
+	let second = &second[second.find("func_list = {").unwrap()..];
+
+	assert!(first.find(r#"["a"]"#) < first.find(r#"["b"]"#));
+	assert!(second.find(r#"["a"]"#) < second.find(r#"["b"]"#));
+}
+
+#[test]
+fn data_count_validates_index() {
+	let source = r#"
+		(module
+			(memory 1)
+			(data "hello")
+			(func
+				(memory.init {} (i32.const 0) (i32.const 0) (i32.const 1))
+			)
+		)
+	"#;
+
+	assert!(translate(&source.replace("{}", "0")).is_ok());
+	assert!(translate(&source.replace("{}", "5")).is_err());
+}
+
+#[test]
+fn nested_blocks_are_indented() {
+	let source = r#"
+		(module
+			(func (param i32)
+				(loop
+					(br_if 0 (local.get 0))
+				)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+	let start = data.find("while true do").unwrap();
+	let line_start = data[..start].rfind('\n').unwrap() + 1;
+	let outer = &data[line_start..start];
+
+	assert!(data.contains(&format!("{outer}\tif loc_0 ~= 0 then\n")));
+	assert!(data.contains(&format!("{outer}\t\tcontinue\n")));
+	assert!(data.contains(&format!("{outer}end\n")));
+}
+
+#[test]
+fn dispose_releases_memory() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func (export "load") (result i32)
+				(i32.load (i32.const 0))
+			)
+		)
+	"#;
+
+	let options = Options {
+		dispose: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+	let dispose = data.split("dispose = function()").nth(1).unwrap();
+
+	assert!(dispose.contains("memory_at_0 = nil"));
+	assert!(dispose.contains("table.clear(MEMORY_LIST)"));
+	assert!(!dispose.contains("TABLE_LIST"));
+	assert!(!translate(source).unwrap().contains("dispose"));
+}
+
+#[test]
+fn i64_bitwise_whole_value() {
+	let source = r#"
+		(module
+			(func (param i64 i64) (result i64 i64)
+				(i64.shl (local.get 0) (i64.const 40))
+				(i64.and (i64.const 0x0000_00FF_0000_00FF) (local.get 1))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.shl_i64(loc_0, rt.i64_from_u32(40, 0))"));
+	assert!(data.contains("rt.bit_and_i64(rt.i64_from_u32(255, 255), loc_1)"));
+}
+
+#[test]
+fn locals_follow_parameters_across_groups() {
+	let source = r#"
+		(module
+			(func (param i32 f64) (result i32 i64 f64)
+				(local i64 i64)
+				(local f64)
+				(local.set 3 (i64.const 7))
+				(local.set 4 (local.get 1))
+				(local.get 0)
+				(local.get 3)
+				(local.get 4)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("function(loc_0, loc_1)"));
+	assert!(data.contains("local loc_2 = rt.i64_ZERO"));
+	assert!(data.contains("local loc_3 = rt.i64_ZERO"));
+	assert!(data.contains("local loc_4 = 0.0"));
+	assert!(data.contains("loc_3 = rt.i64_from_u32(7, 0)"));
+	assert!(data.contains("loc_4 = loc_1"));
+	assert!(data.contains("reg_0 = loc_0"));
+	assert!(data.contains("reg_1 = loc_3"));
+	assert!(data.contains("reg_2 = loc_4"));
+}
+
+#[test]
+fn nolint_header_comes_first() {
+	let options = Options {
+		no_lint: true,
+		..Options::default()
+	};
+
+	let mut data = Vec::new();
+
+	codegen_luau::write_header(&options, &mut data).unwrap();
+
+	let header = String::from_utf8(data).unwrap();
+
+	assert!(header.starts_with("--!nolint\n"));
+
+	let mut data = Vec::new();
+
+	codegen_luau::write_header(&Options::default(), &mut data).unwrap();
+
+	assert!(!String::from_utf8(data).unwrap().contains("nolint"));
+}
+
+#[test]
+fn store_targets_second_memory() {
+	let source = r#"
+		(module
+			(memory $a 1)
+			(memory $b 1)
+			(func
+				(i32.store $b (i32.const 0) (i32.const 7))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.store_i32(memory_at_1, 0, 7)"));
+	assert!(data.contains("memory_at_1 = MEMORY_LIST[1]"));
+	assert!(!data.contains("(memory_at_0,"));
+}
+
+#[test]
+fn data_offset_from_global_expression() {
+	let source = r#"
+		(module
+			(import "env" "base" (global i32))
+			(memory 1)
+			(data (global.get 0) "a")
+			(data (i32.add (global.get 0) (i32.const 4)) "b")
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"MEMORY_LIST[0], GLOBAL_LIST[0].value,"a")"#));
+	assert!(data.contains(r#"MEMORY_LIST[0], rt.add_i32(GLOBAL_LIST[0].value, 4),"b")"#));
+}
+
+#[test]
+fn ref_func_of_uncalled_function() {
+	let source = r#"
+		(module
+			(func $get (result funcref)
+				(ref.func $answer)
+			)
+			(func $answer (result i32)
+				(i32.const 42)
+			)
+			(elem declare func $answer)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("reg_0 = FUNC_LIST[1]"));
+	assert!(data.contains("FUNC_LIST[1] = --[[ answer ]] function()"));
+}
+
+#[test]
+fn source_map_offsets() {
+	let source = r#"
+		(module
+			(func (param i32)
+				(local.set 0 (i32.const 1))
+			)
+		)
+	"#;
+
+	let options = Options {
+		source_map: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	// The header, type, and function sections place the body's `local.set` at 0x1a.
+	assert!(data.contains("--@ 0x1a\n\t\tloc_0 = 1"));
+	assert!(!translate(source).unwrap().contains("--@"));
+
+	let source = r#"
+		(module
+			(func (param i32) (result i32)
+				(block
+					(br_if 0 (local.get 0))
+					(local.set 0 (i32.const 1))
+					(loop
+						(br_if 1 (local.get 0))
+					)
+				)
+				(local.get 0)
+			)
+		)
+	"#;
+
+	let data = translate_with(source, &options).unwrap();
+	let list: Vec<_> = data
+		.lines()
+		.filter_map(|v| v.trim().strip_prefix("--@ 0x"))
+		.map(|v| usize::from_str_radix(v, 16).unwrap())
+		.collect();
+
+	assert!(list.len() > 3);
+	assert!(list.windows(2).all(|v| v[0] < v[1]));
+}
+
+#[test]
+fn audit_precedes_store() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func (param i32)
+				(i32.store16 offset=2 (local.get 0) (i32.const 7))
+			)
+		)
+	"#;
+
+	let options = Options {
+		audit_store: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains(
+		"\t\tdo\n\t\t\tlocal address, value = loc_0 + 2, 7\n\t\t\trt.audit_store(address, 2, value)\n\t\t\trt.store_i32_n16(memory_at_0, address, value)\n\t\tend\n"
+	));
+	assert!(!translate(source).unwrap().contains("rt.audit_store("));
+}
+
+#[test]
+fn unused_function_is_stripped() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func $main (export "main")
+				(call $helper)
+			)
+			(func $helper)
+			(func $unused)
+			(func $indirect)
+			(elem (i32.const 0) $indirect)
+		)
+	"#;
+
+	let options = Options {
+		strip_unused: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("FUNC_LIST[0] = "));
+	assert!(data.contains("FUNC_LIST[1] = "));
+	assert!(!data.contains("FUNC_LIST[2] = "));
+	assert!(data.contains("FUNC_LIST[3] = "));
+	assert!(translate(source).unwrap().contains("FUNC_LIST[2] = "));
+}
+
+#[test]
+fn select_chain_uses_elseif() {
+	let source = r#"
+		(module
+			(func (param i32 i32) (result i32)
+				(select
+					(i32.const 1)
+					(select (i32.const 2) (i32.const 3) (local.get 1))
+					(local.get 0)
+				)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("(if loc_0 ~= 0 then 1 elseif loc_1 ~= 0 then 2 else 3)"));
+}
+
+#[test]
+fn return_call_is_tail_position() {
+	let source = r#"
+		(module
+			(func $even (param i32) (result i32)
+				(if (i32.eqz (local.get 0))
+					(then (return (i32.const 1)))
+				)
+				(return_call $odd (i32.sub (local.get 0) (i32.const 1)))
+			)
+			(func $odd (param i32) (result i32)
+				(if (i32.eqz (local.get 0))
+					(then (return (i32.const 0)))
+				)
+				(return_call $even (i32.sub (local.get 0) (i32.const 1)))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("do return FUNC_LIST[1](rt.sub_i32(loc_0, 1)) end"));
+	assert!(data.contains("do return FUNC_LIST[0](rt.sub_i32(loc_0, 1)) end"));
+}
+
+#[test]
+fn large_function_is_split() {
+	let source = r#"
+		(module
+			(func (param i32) (result i32)
+				(local.set 0 (i32.add (local.get 0) (i32.const 1)))
+				(local.set 0 (i32.add (local.get 0) (i32.const 2)))
+				(block
+					(br_if 1 (local.get 0) (local.get 0))
+					(drop)
+				)
+				(local.set 0 (i32.mul (local.get 0) (i32.const 3)))
+				(local.get 0)
+			)
+		)
+	"#;
+
+	let options = Options {
+		split_size: Some(4),
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.matches("if (function()").count() > 1);
+	assert!(data.contains("return true\n\t\tend)() then\n\t\t\tbreak"));
+	assert!(data.contains("loc_0 = rt.add_i32(loc_0, 1)"));
+	assert!(data.contains("loc_0 = rt.mul_i32(loc_0, 3)"));
+	assert!(!translate(source).unwrap().contains("if (function()"));
+}
+
+#[test]
+fn function_ids_by_name() {
+	let source = r#"
+		(module
+			(func $first)
+			(func $second (export "exported_name"))
+		)
+	"#;
+
+	let options = Options {
+		func_ids: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains(r#"["exported_name"] = 1,"#));
+	assert!(data.contains(r#"["first"] = 0,"#));
+	assert!(data.contains("FUNC_LIST[1] = "));
+	assert!(data.contains("func_ids = FUNC_IDS,"));
+	assert!(!translate(source).unwrap().contains("FUNC_IDS"));
+}
+
+#[test]
+fn indirect_call_checks_signature() {
+	let source = r#"
+		(module
+			(type $none (func))
+			(type $int (func (param i32)))
+			(table 1 funcref)
+			(elem (i32.const 0) $target)
+			(func $target (type $none))
+			(func (export "main")
+				(call_indirect (type $int) (i32.const 5) (i32.const 0))
+			)
+		)
+	"#;
+
+	let options = Options {
+		check_signature: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("FUNC_TYPE[FUNC_LIST[0]] = 0"));
+	assert!(data.contains("local index = 0\n"));
+	assert!(data.contains("rt.check_signature(FUNC_TYPE, TABLE_LIST[0].data[index], 1)"));
+	assert!(!translate(source).unwrap().contains("FUNC_TYPE"));
+}
+
+#[test]
+fn indirect_call_checks_share_index() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func (export "main") (param i32)
+				(call_indirect (i32.add (local.get 0) (i32.const 1)))
+			)
+		)
+	"#;
+
+	let options = Options {
+		check_signature: true,
+		check_table_bounds: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+	let body = function_body(&data, 0);
+
+	assert_eq!(body.matches("rt.add_i32(loc_0, 1)").count(), 1);
+	assert!(body.contains("local index = rt.add_i32(loc_0, 1)\n"));
+	assert!(body.contains("rt.check_signature(FUNC_TYPE, TABLE_LIST[0].data[index], 1)"));
+	assert!(body.contains("TABLE_LIST[0].data[index]()"));
+}
+
+#[test]
+fn stats_match_written_length() {
+	let source = r#"
+		(module
+			(func $small)
+			(func $large (result i32)
+				(i32.add (i32.const 1) (i32.mul (i32.const 2) (i32.const 3)))
+			)
+		)
+	"#;
+
+	let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
+	let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+	let bytes = parsed.encode().unwrap();
+
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let type_info = TypeInfo::from_module(&wasm);
+	let mut data = Vec::new();
+
+	let stats =
+		codegen_luau::from_module_with_stats(&wasm, &type_info, &Options::default(), &mut data)
+			.unwrap();
+
+	assert_eq!(stats.total(), data.len());
+	assert_eq!(stats.function_count(), 2);
+	assert_eq!(stats.largest_function().map(|v| v.0), Some(1));
+}
+
+#[test]
+fn local_index_is_validated() {
+	let source = r#"
+		(module
+			(func (param i32) (result i32)
+				(local i32)
+				(local.get {})
+			)
+		)
+	"#;
+
+	assert!(translate(&source.replace("{}", "1")).is_ok());
+	assert!(translate(&source.replace("{}", "2")).is_err());
+}
+
+#[test]
+fn locals_start_from_literal_zero() {
+	let source = r#"
+		(module
+			(func (local i32 f64)
+				(local.set 0 (i32.const 1))
+				(local.set 1 (f64.const 1))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("local loc_0 = 0\n"));
+	assert!(data.contains("local loc_1 = 0.0\n"));
+	assert!(!data.contains("ZERO_"));
+}
+
+#[test]
+fn exported_i64_uses_boundary_form() {
+	let source = r#"
+		(module
+			(func (export "wide") (result i32 i64)
+				(i32.const 1)
+				(i64.const 5)
+			)
+		)
+	"#;
+
+	let options = Options {
+		export_i64: I64Export::Pair,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("local low_1, high_1 = rt.i64_into_u32(result_1)"));
+	assert!(data.contains("return result_0, low_1, high_1"));
+
+	let options = Options {
+		export_i64: I64Export::String,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("return result_0, rt.i64_into_string(result_1)"));
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"["wide"] = FUNC_LIST[0],"#));
+}
+
+#[test]
+fn hot_globals_are_hoisted() {
+	let source = r#"
+		(module
+			(global $counter (mut i32) (i32.const 0))
+			(func $bump
+				(global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+				(global.set $counter (i32.add (global.get $counter) (i32.const 1)))
+			)
+		)
+	"#;
+
+	let options = Options {
+		hoist_threshold: Some(2),
+		..Options::default()
+	};
+
+	let before = translate(source).unwrap();
+	let after = translate_with(source, &options).unwrap();
+
+	assert_eq!(before.matches("GLOBAL_LIST[0].value").count(), 4);
+	assert_eq!(after.matches("GLOBAL_LIST[0].value").count(), 0);
+	assert!(after.contains("local global_0 = GLOBAL_LIST[0]"));
+	assert_eq!(after.matches("global_0.value").count(), 4);
+}
+
+#[test]
+fn imported_and_defined_memory_bind_in_order() {
+	let source = r#"
+		(module
+			(import "env" "mem" (memory $host 1))
+			(memory $own 1)
+			(func (result i32)
+				(i32.add
+					(i32.load $host (i32.const 0))
+					(i32.load $own (i32.const 0))
+				)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"MEMORY_LIST[0] = wasm["env"].memory_list["mem"]"#));
+	assert!(data.contains("MEMORY_LIST[1] = rt.allocator_new(1, 65535)"));
+	assert!(data.contains("memory_at_0 = MEMORY_LIST[0]"));
+	assert!(data.contains("memory_at_1 = MEMORY_LIST[1]"));
+	assert!(data.contains("rt.load_i32(memory_at_0, 0), rt.load_i32(memory_at_1, 0)"));
+}
+
+#[test]
+fn mixed_memory_is_reported_in_stats() {
+	let stats_of = |source: &str| {
+		let lexed = ParseBuffer::new(source).expect("Failed to tokenize");
+		let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+		let bytes = parsed.encode().unwrap();
+
+		let wasm = Module::try_from_data(&bytes).unwrap();
+		let type_info = TypeInfo::from_module(&wasm);
+
+		codegen_luau::from_module_with_stats(
+			&wasm,
+			&type_info,
+			&Options::default(),
+			&mut Vec::new(),
+		)
+		.unwrap()
+	};
+
+	let mixed = stats_of(r#"(module (import "env" "mem" (memory 1)) (memory 1))"#);
+	let imported = stats_of(r#"(module (import "env" "mem" (memory 1)))"#);
+
+	assert_eq!(
+		mixed.warning_list(),
+		["module both imports and defines memories, defined ones start at index 1"]
+	);
+	assert!(imported.warning_list().is_empty());
+}
+
+#[test]
+fn nan_payload_is_preserved() {
+	let source = r#"
+		(module
+			(func (result f64) (f64.const nan:0x4000000000001))
+			(func (result f32) (f32.const nan:0x200001))
+			(func (result f64) (f64.const nan))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.reinterpret_f64_i64(rt.i64_from_u32(1, 2146697216))"));
+	assert!(data.contains("rt.reinterpret_f32_i32(2141192193)"));
+	assert!(data.contains("-(0.0 / 0.0)"));
+}
+
+// Float literals go through Rust's locale-independent `{:e}` formatting, so
+// these spellings must be byte-identical on every platform.
+#[test]
+fn float_literals_are_golden() {
+	let list = [
+		("f64", "0.5", "5e-1"),
+		("f64", "1", "1e0"),
+		("f64", "-2.25", "-2.25e0"),
+		("f64", "1e300", "1e300"),
+		("f64", "0.1", "1e-1"),
+		("f64", "-0", "-0e0"),
+		("f64", "5e-324", "5e-324"),
+		("f64", "123456.789", "1.23456789e5"),
+		("f32", "0.1", "1.0000000149011612e-1"),
+	];
+
+	for (ty, value, expected) in list {
+		let source = format!("(module (func (result {ty}) ({ty}.const {value})))");
+		let data = translate(&source).unwrap();
+		let line = format!("reg_0 = {expected}\n");
+
+		assert!(data.contains(&line), "{ty} {value}");
+	}
+}
+
+#[test]
+fn typed_select_keeps_null_reference() {
+	let source = r#"
+		(module
+			(func $pick (result funcref)
+				(select (result funcref) (ref.null func) (ref.func $pick) (i32.const 1))
+			)
+			(elem declare func $pick)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("(if 1 ~= 0 then nil else FUNC_LIST[0])"));
+}
+
+#[test]
+fn passive_elements_are_kept_aside() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func $target)
+			(elem $passive func $target)
+			(elem $declared declare func $target)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("ELEM_LIST[0] = { FUNC_LIST[0], }"));
+	assert!(!data.contains("ELEM_LIST[1]"));
+	assert!(!data.contains("table.move"));
+}
+
+#[test]
+fn remainder_avoids_fmod() {
+	let source = r#"
+		(module
+			(func (param i32) (result i32) (i32.rem_s (local.get 0) (i32.const 3)))
+			(func (param i32) (result i32) (i32.rem_u (local.get 0) (i32.const 0x10)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.rem_i32(loc_0, 3)"));
+	assert!(data.contains("rt.rem_u32(loc_0, 16)"));
+	assert!(codegen_luau::RUNTIME.contains("return bit_or(lhs - math_modf(lhs / rhs) * rhs, 0)"));
+	assert!(!codegen_luau::RUNTIME.contains("fmod"));
+}
+
+#[test]
+fn table_ops_use_runtime_helpers() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func (result funcref) (table.get 0 (i32.const 5)))
+			(func (result i32)
+				(table.set 0 (i32.const 0) (ref.null func))
+				(i32.add (table.size 0) (table.grow 0 (ref.null func) (i32.const 2)))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("rt.table_get(TABLE_LIST[0], 5)"));
+	assert!(data.contains("rt.table_set(TABLE_LIST[0], 0, nil)"));
+	assert!(data.contains("= TABLE_LIST[0].min"));
+	assert!(data.contains("= rt.table_grow(TABLE_LIST[0], nil, 2)"));
+	assert!(codegen_luau::RUNTIME.contains("\"out of bounds table access\""));
+}
+
+#[test]
+fn single_export_is_returned_bare() {
+	let source = r#"
+		(module
+			(func (export "add") (param i32 i32) (result i32)
+				(i32.add (local.get 0) (local.get 1))
+			)
+		)
+	"#;
+
+	let options = Options {
+		bare_function: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.ends_with("run_init_code()\nreturn FUNC_LIST[0]\n"));
+	assert!(!data.contains("return function(wasm)"));
+
+	let source = r#"
+		(module
+			(import "env" "memory" (memory 1))
+			(func (export "add") (param i32 i32) (result i32)
+				(i32.add (local.get 0) (local.get 1))
+			)
+		)
+	"#;
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("return function(wasm)"));
+}
+
+#[test]
+fn indirect_call_checks_table_bounds() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func (export "main") (param i32)
+				(call_indirect (param i32) (i32.const 7) (local.get 0))
+			)
+		)
+	"#;
+
+	let options = Options {
+		check_table_bounds: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains(
+		"\t\tdo\n\t\t\tlocal arg_0 = 7\n\t\t\tlocal index = loc_0\n\t\t\trt.check_table_bounds(TABLE_LIST[0], index)\n\t\t\tTABLE_LIST[0].data[index](arg_0)\n\t\tend\n"
+	));
+	assert!(codegen_luau::RUNTIME.contains(r#"rt.trap("undefined element""#));
+	assert!(!translate(source)
+		.unwrap()
+		.contains("rt.check_table_bounds("));
+}
+
+#[test]
+fn runtime_can_be_required() {
+	let source = r#"
+		(module
+			(func (export "add") (param i32 i32) (result i32)
+				(i32.add (local.get 0) (local.get 1))
+			)
+		)
+	"#;
+
+	let mut runtime = Vec::new();
+
+	codegen_luau::write_runtime_module(&mut runtime).unwrap();
+
+	let runtime = String::from_utf8(runtime).unwrap();
+
+	assert!(runtime.starts_with(codegen_luau::RUNTIME));
+	assert!(runtime.contains("\n\trt = rt,\n"));
+	assert!(runtime.ends_with("}\n"));
+
+	let options = Options {
+		runtime_require: Some("script.Parent.Runtime".to_string()),
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.starts_with("local runtime = require(script.Parent.Runtime)\n"));
+	assert!(data.contains("local rt = runtime.rt\n"));
+	assert!(data.contains("local bit_and = runtime.bit_and\n"));
+	assert!(!data.contains("function rt.add_i32"));
+}
+
+#[test]
+fn list_size_hint_is_highest_index() {
+	let source = r#"
+		(module
+			(import "env" "a" (func))
+			(import "env" "b" (func))
+			(func)
+			(func)
+			(func)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("local FUNC_LIST = table.create(4)"));
+	assert!(data.contains("FUNC_LIST[4] = "));
+	assert!(!data.contains("FUNC_LIST[5]"));
+
+	let data = translate("(module (func))").unwrap();
+
+	assert!(data.contains("local FUNC_LIST = table.create(0)"));
+	assert!(data.contains("FUNC_LIST[0] = "));
+}
+
+#[test]
+fn atomic_add_uses_plain_access() {
+	let source = r#"
+		(module
+			(memory 1 1 shared)
+			(func (param i32) (result i32)
+				(i32.atomic.rmw.add offset=4 (local.get 0) (i32.const 5))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(
+		"reg_0 = rt.atomic.rmw(memory_at_0, loc_0 + 4, 5, rt.load_i32, rt.store_i32, rt.add_i32)"
+	));
+	assert!(data.contains("MEMORY_LIST[0] = rt.allocator_new(1, 1)"));
+}
+
+#[test]
+fn atomic_cmpxchg_wraps_expected() {
+	let source = r#"
+		(module
+			(memory 1 1 shared)
+			(func (result i32)
+				(atomic.fence)
+				(i32.atomic.rmw8.cmpxchg_u (i32.const 0) (i32.const 0x101) (i32.const 2))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(
+		"reg_0 = rt.atomic.cmpxchg(memory_at_0, 0, 257, 2, rt.load_i32_u8, rt.store_i32_n8)"
+	));
+	assert!(codegen_luau::RUNTIME.contains("store(scratch, 0, expected)"));
+}
+
+#[test]
+fn strict_f32_rounds_inexact_sum() {
+	// The exact sum `1 + 2^-30` needs 31 bits of mantissa, so it rounds to `1` in `f32`.
+	let source = r#"
+		(module
+			(func (result f32)
+				(f32.add (f32.const 1) (f32.const 0x1p-30))
+			)
+		)
+	"#;
+
+	let options = Options {
+		strict_f32: true,
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("rt.demote_f32_f64((1e0 + 9.313225746154785e-10))"));
+	assert!(codegen_luau::RUNTIME.contains(
+		"function rt.demote_f32_f64(num)\n\tbuffer_write_f32(RE_INSTANCE, 0, num)\n\n\treturn buffer_read_f32(RE_INSTANCE, 0)"
+	));
+}
+
+#[test]
+fn symbol_prefix_applies_to_lists() {
+	let source = r#"
+		(module
+			(import "env" "f" (func $f))
+			(table 1 funcref)
+			(memory 1)
+			(global $g (mut i32) (i32.const 0))
+			(elem (i32.const 0) $run)
+			(func $run (export "run")
+				(call $f)
+				(i32.store (i32.const 0) (global.get $g))
+				(call_indirect (i32.const 0))
+			)
+		)
+	"#;
+
+	let options = Options {
+		symbol_prefix: "mod_".to_string(),
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert!(data.contains("local mod_FUNC_LIST = table.create(1)"));
+	assert!(data.contains("rt.store_i32(mod_memory_at_0, 0, mod_GLOBAL_LIST[0].value)"));
+	assert!(data.contains("local data = { mod_FUNC_LIST[1], }"));
+	assert!(!data.replace("mod_FUNC_LIST", "").contains("FUNC_LIST"));
+}
+
+#[test]
+fn memory64_converts_addresses() {
+	let source = r#"
+		(module
+			(memory i64 1)
+			(data (i64.const 4) "hi")
+			(func (param i64) (result i64)
+				(i32.store (i64.const 16) (i32.const 1))
+				(i64.extend_i32_u (i32.load (local.get 0)))
+				(memory.size)
+				(i64.add)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(
+		data.contains("rt.store_i32(memory_at_0, rt.convert_f64_u64(rt.i64_from_u32(16, 0)), 1)")
+	);
+	assert!(data.contains("rt.load_i32(memory_at_0, rt.convert_f64_u64(loc_0))"));
+	assert!(data.contains("rt.extend_i64_i32(rt.allocator_size(memory_at_0))"));
+	assert!(data.contains("MEMORY_LIST[0], rt.convert_f64_u64(rt.i64_from_u32(4, 0)),\"hi\")"));
+}
+
+fn manifest_of(source: &str) -> Vec<(String, External, u32)> {
+	let bytes = encode(source);
+	let wasm = Module::try_from_data(&bytes).unwrap();
+
+	codegen_luau::export_manifest(&wasm)
+		.into_iter()
+		.map(|v| (v.name().to_string(), v.kind(), v.index()))
+		.collect()
+}
+
+#[test]
+fn export_manifest_lists_every_kind() {
+	let source = r#"
+		(module
+			(func (export "b"))
+			(func (export "a"))
+			(memory (export "mem") 1)
+			(table (export "tab") 1 funcref)
+			(global (export "glob") i32 (i32.const 0))
+		)
+	"#;
+
+	let expected = [
+		("a", External::Func, 1),
+		("b", External::Func, 0),
+		("tab", External::Table, 0),
+		("mem", External::Memory, 0),
+		("glob", External::Global, 0),
+	]
+	.map(|(name, kind, index)| (name.to_string(), kind, index));
+
+	assert_eq!(manifest_of(source), expected);
+}
+
+#[test]
+fn export_manifest_of_no_exports_is_empty() {
+	let source = r#"
+		(module
+			(func)
+			(memory 1)
+		)
+	"#;
+
+	assert!(manifest_of(source).is_empty());
+}
+
+#[test]
+fn leaf_if_has_no_loop() {
+	let source = r#"
+		(module
+			(func (param i32) (result i32)
+				(if (local.get 0)
+					(then (local.set 0 (i32.const 5)))
+					(else (local.set 0 (i32.const 6)))
+				)
+				(local.get 0)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert_eq!(data.matches("while true do").count(), 1);
+	assert!(
+		data.contains("if loc_0 ~= 0 then\n\t\t\tloc_0 = 5\n\t\telse\n\t\t\tloc_0 = 6\n\t\tend")
+	);
+}
+
+#[test]
+fn branch_out_of_if_breaks_target_loop() {
+	let source = r#"
+		(module
+			(func (param i32)
+				(block $out
+					(if (local.get 0) (then (br $out)))
+					(call 0 (i32.const 0))
+				)
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("if loc_0 ~= 0 then\n\t\t\t\tbreak\n\t\t\tend"));
+	assert!(!data.contains("desired ="));
+}
+
+#[test]
+fn imported_globals_are_boxed() {
+	let source = r#"
+		(module
+			(import "env" "counter" (global $counter (mut i32)))
+			(import "env" "step" (global $step i32))
+			(func
+				(global.set $counter (i32.add (global.get $counter) (global.get $step)))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data
+		.contains("GLOBAL_LIST[0].value = rt.add_i32(GLOBAL_LIST[0].value, GLOBAL_LIST[1].value)"));
+	assert!(data.contains(
+		r#"assert(type(GLOBAL_LIST[0]) == "table", "mutable global import must be a table")"#
+	));
+	assert!(data.contains(
+		r#"if type(GLOBAL_LIST[1]) ~= "table" then GLOBAL_LIST[1] = { value = GLOBAL_LIST[1] } end"#
+	));
+}
+
+#[test]
+fn type_annotations_on_signature_and_locals() {
+	let source = r#"
+		(module
+			(func (param i32 i64) (result f64)
+				(local i32 i64)
+				(f64.const 0)
+			)
+			(func)
+		)
+	"#;
+
+	let options = Options {
+		type_annotations: true,
+		..Options::default()
+	};
+
+	let typed = translate_with(source, &options).unwrap();
+
+	assert!(typed.contains("function(loc_0: number, loc_1: Vector3): number\n"));
+	assert!(typed.contains("local loc_2: number = 0\n"));
+	assert!(typed.contains("local loc_3: Vector3 = rt.i64_ZERO\n"));
+	assert!(typed.contains("function(): ()\n"));
+
+	let untyped = translate(source).unwrap();
+
+	assert!(untyped.contains("function(loc_0, loc_1)\n"));
+	assert!(untyped.contains("local loc_2 = 0\n"));
+	assert!(!untyped.contains(": number"));
+}
+
+#[test]
+fn type_annotations_on_registers() {
+	let source = r#"
+		(module
+			(func $get (result i64) (i64.const 1))
+			(func (result i64)
+				(i64.add (call $get) (call $get))
+			)
+		)
+	"#;
+
+	let options = Options {
+		type_annotations: true,
+		..Options::default()
+	};
+
+	let typed = translate_with(source, &options).unwrap();
+	let body = function_body(&typed, 1);
+
+	assert!(body.contains("local reg_0: any\n"));
+	assert!(body.contains("local reg_1: any\n"));
+
+	let untyped = translate(source).unwrap();
+	let body = function_body(&untyped, 1);
+
+	assert!(body.contains("local reg_0\n"));
+	assert!(body.contains("local reg_1\n"));
+}
+
+#[test]
+fn start_resolves_through_imports() {
+	let imported = r#"
+		(module
+			(import "env" "init" (func $init))
+			(func $own)
+			(start $init)
+		)
+	"#;
+
+	let data = translate(imported).unwrap();
+
+	assert!(data.contains(r#"FUNC_LIST[0] = wasm["env"].func_list["init"]"#));
+	assert!(data.contains("\tFUNC_LIST[0]()\n"));
+	assert!(!data.contains("\tFUNC_LIST[1]()\n"));
+
+	let local = r#"
+		(module
+			(import "env" "init" (func $init))
+			(func $own)
+			(start $own)
+		)
+	"#;
+
+	let data = translate(local).unwrap();
+
+	assert!(data.contains("FUNC_LIST[1] = --[[ own ]] function()"));
+	assert!(data.contains("\tFUNC_LIST[1]()\n"));
+	assert!(!data.contains("\tFUNC_LIST[0]()\n"));
+}
+
+#[test]
+fn imported_and_defined_calls_share_index_space() {
+	let source = r#"
+		(module
+			(import "env" "log" (func $log (param i32)))
+			(func $helper (result i32) (i32.const 7))
+			(func (export "run") (call $log (call $helper)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains(r#"FUNC_LIST[0] = wasm["env"].func_list["log"]"#));
+	assert!(data.contains("FUNC_LIST[1] = --[[ helper ]] function()"));
+	assert!(data.contains("FUNC_LIST[2] = function()"));
+	assert!(data.contains("reg_0 = FUNC_LIST[1]()"));
+	assert!(data.contains("FUNC_LIST[0](reg_0)"));
+	assert!(data.contains(r#"["run"] = FUNC_LIST[2],"#));
+}
+
+#[test]
+fn constant_operands_fold() {
+	let source = r#"
+		(module
+			(func (result i32) (i32.add (i32.const 1) (i32.const 2)))
+			(func (result i32) (i32.mul (i32.const 0x10001) (i32.const 0x10000)))
+			(func (result i32) (i32.div_s (i32.const 1) (i32.const 0)))
+			(func (param i32) (result i32) (i32.add (local.get 0) (i32.const 2)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("reg_0 = 3\n"));
+	assert!(data.contains("reg_0 = 65536\n"));
+	assert!(data.contains("reg_0 = rt.div_i32(1, 0)\n"));
+	assert!(data.contains("reg_0 = rt.add_i32(loc_0, 2)\n"));
+}
+
+#[test]
+fn initializers_use_function_lowering() {
+	let source = r#"
+		(module
+			(import "env" "base" (global $base i32))
+			(global i32 (global.get $base))
+			(memory 1)
+			(data (offset i32.const 8 i32.const 4 i32.add) "a")
+			(data (offset global.get $base i32.const 4 i32.add) "b")
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("GLOBAL_LIST[1] = { value = GLOBAL_LIST[0].value }"));
+	assert!(data.contains(r#"MEMORY_LIST[0], 12,"a")"#));
+	assert!(data.contains(r#"MEMORY_LIST[0], rt.add_i32(GLOBAL_LIST[0].value, 4),"b")"#));
+}
+
+#[test]
+fn text_input_translates() {
+	let source = r#"
+		(module
+			(func (export "answer") (result i32)
+				(i32.const 42)
+			)
+		)
+	"#;
+
+	let data = codegen_luau::from_wat(source.as_bytes(), &Options::default()).unwrap();
+
+	assert!(data.contains("reg_0 = 42"));
+	assert!(data.contains(r#"["answer"] = FUNC_LIST[0],"#));
+	assert!(codegen_luau::from_wat(b"(module (func", &Options::default()).is_err());
+}
+
+#[test]
+fn bundled_output_needs_no_require() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func (export "load") (param i32) (result i32) (i32.load (local.get 0)))
+		)
+	"#;
+
+	let options = Options {
+		runtime_require: Some("script.Runtime".to_string()),
+		..Options::default()
+	};
+
+	let bytes = encode(source);
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let mut data = Vec::new();
+
+	codegen_luau::from_module_bundled(&wasm, &options, &mut data).unwrap();
+
+	let data = String::from_utf8(data).unwrap();
+
+	assert!(!data.contains("require("));
+	assert!(data.contains("function rt.allocator_new("));
+	assert!(data.contains("rt.load_i32(memory_at_0, loc_0)"));
+}
+
+#[test]
+fn bundled_output_stays_under_local_limit() {
+	let source = r#"
+		(module
+			(type $binary (func (param i64 i64) (result i64)))
+			(memory 1)
+			(memory 1)
+			(table 2 funcref)
+			(global (mut i32) (i32.const 0))
+			(data (memory 1) (i32.const 0) "a")
+			(elem (i32.const 0) $add $sub)
+			(func $add (export "add") (type $binary) (i64.add (local.get 0) (local.get 1)))
+			(func $sub (export "sub") (type $binary) (i64.sub (local.get 0) (local.get 1)))
+			(func (export "load") (param i32) (result i32)
+				(i32.add (i32.load (local.get 0)) (i32.load 1 (local.get 0)))
+			)
+		)
+	"#;
+
+	let options = Options {
+		func_ids: true,
+		check_signature: true,
+		..Options::default()
+	};
+
+	let bytes = encode(source);
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let mut data = Vec::new();
+
+	codegen_luau::from_module_bundled(&wasm, &options, &mut data).unwrap();
+
+	let data = String::from_utf8(data).unwrap();
+	let count = data.lines().filter(|v| v.starts_with("local ")).count();
+
+	// Luau allows 200 locals per function and the main chunk is one, so leave
+	// room for more modules sharing it.
+	assert!(count < 100, "{count} top-level locals");
+}
+
+#[test]
+fn flat_exports_sit_on_instance() {
+	let source = r#"
+		(module
+			(memory (export "memory") 1)
+			(func (export "main"))
+		)
+	"#;
+
+	let options = Options {
+		flat_exports: true,
+		..Options::default()
+	};
+
+	let flat = translate_with(source, &options).unwrap();
+
+	assert!(flat.contains("\t\t[\"main\"] = FUNC_LIST[0],\n"));
+	assert!(flat.contains("\t\t[\"memory\"] = MEMORY_LIST[0],\n"));
+	assert!(!flat.contains("func_list = {"));
+
+	let nested = translate(source).unwrap();
+
+	assert!(nested.contains("func_list = {\n\t\t\t[\"main\"] = FUNC_LIST[0],"));
+
+	let colliding = r#"
+		(module
+			(func (export "rt"))
+		)
+	"#;
+
+	assert!(translate_with(colliding, &options).is_err());
+}
+
+#[test]
+fn element_segment_checks_table_bounds() {
+	let source = r#"
+		(module
+			(table 1 funcref)
+			(func $f)
+			(elem (i32.const 0) $f $f)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+
+	assert!(data.contains("\t\tif offset + 2 > target.min then\n"));
+	assert!(data.contains(r#"rt.trap("out of bounds table access", "2 elements at ""#));
+	assert!(data.contains("\t\ttable.move(data, 1, 2, offset, target.data)\n"));
+}
+
+#[test]
+fn compact_data_shrinks_binary_segments() {
+	let source = r#"
+		(module
+			(memory 1)
+			(data (i32.const 0) "\00\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14\15\16\17")
+			(data (i32.const 64) "only printable bytes here")
+		)
+	"#;
+
+	let options = Options {
+		compact_data: true,
+		..Options::default()
+	};
+
+	let compact = translate_with(source, &options).unwrap();
+	let escaped = translate(source).unwrap();
+
+	assert!(compact.len() < escaped.len());
+	assert!(compact.contains(r#"rt.base64_decode("AAECAwQFBgcICQoLDA0ODxAREhMUFRYX")"#));
+	assert!(compact.contains(r#"MEMORY_LIST[0], 64,"only printable bytes here")"#));
+	assert!(escaped.contains(r#"MEMORY_LIST[0], 64,"only printable bytes here")"#));
+}
+
+#[test]
+fn no_continue_desugars_loops() {
+	let source = r#"
+		(module
+			(func (param i32) (result i32) (local i32)
+				(block $done
+					(loop $top
+						(br_if $done (i32.ge_u (local.get 1) (local.get 0)))
+						(local.set 1 (i32.add (local.get 1) (i32.const 1)))
+						(br $top)
+					)
+				)
+				(local.get 1)
+			)
+		)
+	"#;
+
+	let options = Options {
+		no_continue: true,
+		..Options::default()
+	};
+
+	let desugared = translate_with(source, &options).unwrap();
+
+	assert!(!desugared.contains("continue"));
+	assert!(desugared.contains("\tlocal desired\n"));
+	assert!(desugared.contains("if desired == 2 then\n"));
+	assert!(translate(source).unwrap().contains("continue"));
+}
+
+#[test]
+fn dependencies_of_add_only_function() {
+	let source = r#"
+		(module
+			(import "env" "f" (func))
+			(func (param i32 i32) (result i32)
+				(i32.add (i32.add (local.get 0) (local.get 1)) (i32.const 1))
+			)
+		)
+	"#;
+
+	let bytes = encode(source);
+	let wasm = Module::try_from_data(&bytes).unwrap();
+	let type_info = TypeInfo::from_module(&wasm);
+	let found = codegen_luau::function_dependencies(&wasm, &type_info, 1).unwrap();
+
+	assert_eq!(
+		found.runtime_set().iter().collect::<Vec<_>>(),
+		[&("rt.add", "i32")]
+	);
+	assert!(found.memory_set().is_empty());
+	assert!(codegen_luau::function_dependencies(&wasm, &type_info, 0).is_err());
+	assert!(codegen_luau::function_dependencies(&wasm, &type_info, 2).is_err());
+}
+
+#[test]
+fn shifts_mask_their_count() {
+	let source = r#"
+		(module
+			(func (param i32 i32) (result i32) (i32.shl (local.get 0) (local.get 1)))
+			(func (param i32 i32) (result i32) (i32.shr_u (local.get 0) (local.get 1)))
+			(func (param i32 i32) (result i32) (i32.rotl (local.get 0) (local.get 1)))
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+	let runtime = codegen_luau::RUNTIME;
+
+	assert!(data.contains("rt.shl_i32(loc_0, loc_1)"));
+	assert!(data.contains("rt.shr_u32(loc_0, loc_1)"));
+	assert!(data.contains("rt.rotl_i32(loc_0, loc_1)"));
+	assert!(!data.contains("<<") && !data.contains(">>"));
+	assert!(runtime.contains("return bit_lshift(lhs, rhs % 32)"));
+	assert!(runtime.contains("return bit_rshift(lhs, rhs % 32)"));
+	assert!(runtime.contains("local count = rhs.X % 64"));
+}
+
+struct Profile;
+
+impl Hook for Profile {
+	fn function_entry(&self, function: Option<usize>) -> Option<String> {
+		Some(format!("profile({})", function.unwrap()))
+	}
+
+	fn loop_entry(&self, function: Option<usize>, level: usize) -> Option<String> {
+		Some(format!("profile_loop({}, {level})", function.unwrap()))
+	}
+}
+
+#[test]
+fn hook_instruments_functions_and_loops() {
+	let source = r#"
+		(module
+			(func $a)
+			(func $b (param i32) (result i32) (i32.add (local.get 0) (i32.const 1)))
+			(func $c (param i32)
+				(loop $top
+					(br_if $top (local.tee 0 (i32.sub (local.get 0) (i32.const 1))))
+				)
+			)
+		)
+	"#;
+
+	let options = Options {
+		hook: Some(Rc::new(Profile)),
+		..Options::default()
+	};
+
+	let data = translate_with(source, &options).unwrap();
+
+	assert_eq!(data.matches("profile(").count(), 3);
+	assert!(data.contains("profile(0)\n") && data.contains("profile(2)\n"));
+	assert_eq!(data.matches("profile_loop(2, 1)\n").count(), 1);
+	assert!(!translate(source).unwrap().contains("profile"));
+}
+
+#[test]
+fn drop_keeps_side_effects() {
+	let source = r#"
+		(module
+			(memory 1)
+			(func $effect (result i32) (i32.const 1))
+			(func (param i32)
+				(drop (call $effect))
+				(drop (i32.load (local.get 0)))
+				(drop (i32.div_s (i32.const 1) (local.get 0)))
+				(drop (i32.add (local.get 0) (i32.const 1)))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+	let body = function_body(&data, 1);
+
+	assert!(body.contains("reg_0 = FUNC_LIST[0]()\n"));
+	assert!(body.contains("reg_0 = rt.load_i32(memory_at_0, loc_0)\n"));
+
+	// The trapping division is the last statement, as the pure addition leaves nothing behind.
+	assert!(body.contains("reg_0 = rt.div_i32(1, loc_0)\n\t\tbreak\n"));
+	assert!(!body.contains("rt.add_i32"));
+	assert!(!body.contains("loc_0 + 1"));
+}
+
+#[test]
+fn memory_size_is_read_before_grow() {
+	let source = r#"
+		(module
+			(memory 2 4)
+			(func (result i32)
+				(memory.size)
+				(drop (memory.grow (i32.const 1)))
+			)
+		)
+	"#;
+
+	let data = translate(source).unwrap();
+	let size = data.find("= rt.allocator_size(memory_at_0)").unwrap();
+	let grow = data.find("= rt.allocator_grow(memory_at_0, 1)").unwrap();
+
+	assert!(size < grow);
+}
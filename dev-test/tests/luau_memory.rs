@@ -0,0 +1,69 @@
+use std::{
+	alloc::{GlobalAlloc, Layout, System},
+	io::sink,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+use codegen_luau::Options;
+use wasm_ast::module::Module;
+use wast::{parser::ParseBuffer, Wat};
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		let size = layout.size();
+		let current = CURRENT.fetch_add(size, Ordering::SeqCst) + size;
+
+		PEAK.fetch_max(current, Ordering::SeqCst);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+		System.dealloc(ptr, layout);
+	}
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+fn encode_synthetic(func_count: usize) -> Vec<u8> {
+	let body = "(local.set 0 (i32.add (local.get 0) (i32.const 1)))".repeat(500);
+	let func = format!("(func (param i32) (result i32) {body} local.get 0)");
+	let source = format!("(module {})", func.repeat(func_count));
+
+	let lexed = ParseBuffer::new(&source).expect("Failed to tokenize");
+	let mut parsed: Wat = wast::parser::parse(&lexed).unwrap();
+
+	parsed.encode().unwrap()
+}
+
+// Returns the most memory held at once while translating, past what the
+// parsed module itself already holds.
+fn peak_translating(bytes: &[u8]) -> usize {
+	let wasm = Module::try_from_data(bytes).unwrap();
+	let base = CURRENT.load(Ordering::SeqCst);
+
+	PEAK.store(base, Ordering::SeqCst);
+	codegen_luau::from_module_untyped(&wasm, &Options::default(), &mut sink()).unwrap();
+
+	PEAK.load(Ordering::SeqCst) - base
+}
+
+#[test]
+fn function_count_does_not_scale_peak() {
+	let small = encode_synthetic(8);
+	let large = encode_synthetic(64);
+
+	let small_peak = peak_translating(&small);
+	let large_peak = peak_translating(&large);
+
+	assert!(
+		large_peak < small_peak * 2,
+		"peak grew from {small_peak} to {large_peak} bytes"
+	);
+}
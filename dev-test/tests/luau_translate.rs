@@ -1,8 +1,10 @@
 use std::{
+	cell::RefCell,
 	io::{Result, Write},
 	path::PathBuf,
 };
 
+use codegen_luau::Options;
 use wasm_ast::module::{Module, TypeInfo};
 use wast::{
 	core::{WastArgCore, WastRetCore},
@@ -13,6 +15,11 @@ use target::{get_name_from_id, Target};
 
 mod target;
 
+thread_local! {
+	// Tests run on their own threads, so each may translate with its own options.
+	static OPTIONS: RefCell<Options> = RefCell::default();
+}
+
 static ASSERTION: &str = include_str!("luau_assert.lua");
 
 struct Luau;
@@ -29,7 +36,7 @@ impl Luau {
 		let data_1 = u32::from_ne_bytes(data[0..4].try_into().unwrap());
 		let data_2 = u32::from_ne_bytes(data[4..8].try_into().unwrap());
 
-		write!(w, "rt.i64.from_u32({data_1}, {data_2})")
+		write!(w, "rt.i64_from_u32({data_1}, {data_2})")
 	}
 
 	fn write_arg(data: &WastArg, w: &mut dyn Write) -> Result<()> {
@@ -103,7 +110,7 @@ impl Target for Luau {
 				let data = Module::try_from_data(&bytes).unwrap();
 
 				writeln!(w, "assert_trap((function()")?;
-				codegen_luau::from_module_untyped(&data, w)?;
+				codegen_luau::from_module_untyped(&data, &Options::default(), w)?;
 				writeln!(w, "end)(), linked)")
 			}
 		}
@@ -157,7 +164,9 @@ impl Target for Luau {
 		let type_info = TypeInfo::from_module(data);
 
 		writeln!(w, r#"loaded["temp"] = (function()"#)?;
-		codegen_luau::from_module_typed(data, &type_info, w)?;
+		OPTIONS.with(|options| {
+			codegen_luau::from_module_typed(data, &type_info, &options.borrow(), w)
+		})?;
 		writeln!(w, "end)()(linked)")?;
 
 		if let Some(name) = name {
@@ -242,3 +251,112 @@ fn translate_file(path: PathBuf) {
 
 	Luau::test(name, &source).unwrap();
 }
+
+#[test]
+fn mixed_memory() {
+	let source = include_str!("mixed_memory.wast");
+
+	Luau::test("mixed_memory.wast", source).unwrap();
+}
+
+#[test]
+fn memory_grow_failure() {
+	let source = include_str!("memory_grow_failure.wast");
+
+	Luau::test("memory_grow_failure.wast", source).unwrap();
+}
+
+#[test]
+fn unaligned_access() {
+	let source = include_str!("unaligned_access.wast");
+
+	Luau::test("unaligned_access.wast", source).unwrap();
+}
+
+#[test]
+fn little_endian() {
+	let source = include_str!("little_endian.wast");
+
+	Luau::test("little_endian.wast", source).unwrap();
+}
+
+#[test]
+fn compact_data() {
+	let source = include_str!("compact_data.wast");
+
+	OPTIONS.with(|options| options.borrow_mut().compact_data = true);
+	Luau::test("compact_data.wast", source).unwrap();
+}
+
+#[test]
+fn integer_division() {
+	let source = include_str!("integer_division.wast");
+
+	Luau::test("integer_division.wast", source).unwrap();
+}
+
+#[test]
+fn unsigned_shift() {
+	let source = include_str!("unsigned_shift.wast");
+
+	Luau::test("unsigned_shift.wast", source).unwrap();
+}
+
+#[test]
+fn drop_effects() {
+	let source = include_str!("drop_effects.wast");
+
+	Luau::test("drop_effects.wast", source).unwrap();
+}
+
+#[test]
+fn memory_size() {
+	let source = include_str!("memory_size.wast");
+	let epilogue = r#"
+local memory = rt.allocator_new(2, 4)
+
+assert(rt.allocator_size(memory) == 2 and rt.allocator_byte_size(memory) == 131072)
+assert(rt.allocator_grow(memory, 1) == 2)
+assert(rt.allocator_size(memory) == 3 and rt.allocator_byte_size(memory) == 196608)
+"#;
+
+	Luau::test_with_epilogue("memory_size.wast", source, epilogue).unwrap();
+}
+
+#[test]
+fn no_continue() {
+	let source = include_str!("no_continue.wast");
+
+	OPTIONS.with(|options| options.borrow_mut().no_continue = true);
+	Luau::test("no_continue.wast", source).unwrap();
+}
+
+#[test]
+fn trap_kinds() {
+	let source = include_str!("trap_kinds.wast");
+	let epilogue = r#"
+for _, case in {
+	{ "unreachable", "unreachable" },
+	{ "integer divide by zero", "divide", 0 },
+	{ "integer overflow", "overflow", 0xFFFFFFFF },
+	{ "out of bounds memory access", "load", 65536 },
+	{ "out of bounds table access", "table", 2 },
+	{ "undefined element", "call", 2 },
+	{ "uninitialized element", "call", 1 },
+	{ "indirect call type mismatch", "call", 0 },
+} do
+	local success, message = pcall(loaded["temp"].func_list[case[2]], case[3])
+
+	assert(not success and rt.trap_kind(message) == case[1], case[2] .. " did not raise " .. case[1])
+end
+"#;
+
+	OPTIONS.with(|options| {
+		let mut options = options.borrow_mut();
+
+		options.check_signature = true;
+		options.check_table_bounds = true;
+	});
+
+	Luau::test_with_epilogue("trap_kinds.wast", source, epilogue).unwrap();
+}
@@ -0,0 +1,20 @@
+(module
+	(memory 1 2)
+	(data (i32.const 0) "\2a")
+
+	(func (export "grow") (param i32) (result i32) (memory.grow (local.get 0)))
+	(func (export "size") (result i32) (memory.size))
+	(func (export "load") (param i32) (result i32) (i32.load8_u (local.get 0)))
+)
+
+(assert_return (invoke "grow" (i32.const 2)) (i32.const -1))
+(assert_return (invoke "size") (i32.const 1))
+(assert_return (invoke "grow" (i32.const -1)) (i32.const -1))
+(assert_return (invoke "size") (i32.const 1))
+(assert_return (invoke "load" (i32.const 0)) (i32.const 42))
+(assert_trap (invoke "load" (i32.const 65536)) "out of bounds memory access")
+
+(assert_return (invoke "grow" (i32.const 1)) (i32.const 1))
+(assert_return (invoke "size") (i32.const 2))
+(assert_return (invoke "load" (i32.const 0)) (i32.const 42))
+(assert_return (invoke "load" (i32.const 65536)) (i32.const 0))
@@ -0,0 +1,40 @@
+(module
+	(memory 2 5)
+
+	(func $grow_one
+		(drop (memory.grow (i32.const 1)))
+	)
+
+	(func (export "size") (result i32)
+		(memory.size)
+	)
+
+	(func (export "grow") (param i32) (result i32)
+		(memory.grow (local.get 0))
+	)
+
+	(func (export "size_then_grow") (result i32)
+		(memory.size)
+		(drop (memory.grow (i32.const 1)))
+	)
+
+	(func (export "size_then_call") (result i32)
+		(memory.size)
+		(call $grow_one)
+	)
+
+	(func (export "last_byte") (result i32)
+		(i32.load8_u (i32.sub (i32.mul (memory.size) (i32.const 65536)) (i32.const 1)))
+	)
+)
+
+(assert_return (invoke "size") (i32.const 2))
+(assert_return (invoke "grow" (i32.const 1)) (i32.const 2))
+(assert_return (invoke "size") (i32.const 3))
+(assert_return (invoke "last_byte") (i32.const 0))
+(assert_return (invoke "size_then_grow") (i32.const 3))
+(assert_return (invoke "size") (i32.const 4))
+(assert_return (invoke "size_then_call") (i32.const 4))
+(assert_return (invoke "size") (i32.const 5))
+(assert_return (invoke "grow" (i32.const 1)) (i32.const -1))
+(assert_return (invoke "size") (i32.const 5))
@@ -0,0 +1,35 @@
+(module
+	(import "spectest" "memory" (memory $host 1 2))
+	(memory $own 3)
+
+	(data (memory $host) (i32.const 0) "\01")
+	(data (memory $own) (i32.const 0) "\02")
+
+	(func (export "host_size") (result i32)
+		(memory.size $host)
+	)
+
+	(func (export "own_size") (result i32)
+		(memory.size $own)
+	)
+
+	(func (export "host_byte") (result i32)
+		(i32.load8_u $host (i32.const 0))
+	)
+
+	(func (export "own_byte") (result i32)
+		(i32.load8_u $own (i32.const 0))
+	)
+
+	(func (export "store_own") (param i32)
+		(i32.store8 $own (i32.const 0) (local.get 0))
+	)
+)
+
+(assert_return (invoke "host_size") (i32.const 1))
+(assert_return (invoke "own_size") (i32.const 3))
+(assert_return (invoke "host_byte") (i32.const 1))
+(assert_return (invoke "own_byte") (i32.const 2))
+(invoke "store_own" (i32.const 7))
+(assert_return (invoke "own_byte") (i32.const 7))
+(assert_return (invoke "host_byte") (i32.const 1))
@@ -0,0 +1,35 @@
+(module
+	(func (export "count") (param i32) (result i32) (local i32)
+		(block $done
+			(loop $top
+				(br_if $done (i32.ge_u (local.get 1) (local.get 0)))
+				(local.set 1 (i32.add (local.get 1) (i32.const 1)))
+				(br $top)
+			)
+		)
+		(local.get 1)
+	)
+
+	(func (export "nested") (param i32) (result i32) (local $i i32) (local $j i32) (local $sum i32)
+		(block $exit
+			(loop $outer
+				(br_if $exit (i32.ge_u (local.get $i) (local.get 0)))
+				(local.set $i (i32.add (local.get $i) (i32.const 1)))
+				(local.set $j (i32.const 0))
+				(loop $inner
+					(local.set $sum (i32.add (local.get $sum) (i32.const 1)))
+					(local.set $j (i32.add (local.get $j) (i32.const 1)))
+					(br_if $outer (i32.ge_u (local.get $j) (local.get $i)))
+					(br $inner)
+				)
+			)
+		)
+		(local.get $sum)
+	)
+)
+
+(assert_return (invoke "count" (i32.const 0)) (i32.const 0))
+(assert_return (invoke "count" (i32.const 10)) (i32.const 10))
+(assert_return (invoke "nested" (i32.const 0)) (i32.const 0))
+(assert_return (invoke "nested" (i32.const 4)) (i32.const 10))
+(assert_return (invoke "nested" (i32.const 100)) (i32.const 5050))
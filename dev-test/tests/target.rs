@@ -143,11 +143,19 @@ pub trait Target: Sized {
 	}
 
 	fn test(name: &str, source: &str) -> Result<()> {
-		let data = Self::run_generation(source)?;
+		Self::test_with_epilogue(name, source, "")
+	}
+
+	// The epilogue runs after every directive, for checks the `wast` format has no
+	// assertion for.
+	fn test_with_epilogue(name: &str, source: &str, epilogue: &str) -> Result<()> {
+		let mut data = Self::run_generation(source)?;
 		let temp = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
 			.join(name)
 			.with_extension("wast.lua");
 
+		data.extend_from_slice(epilogue.as_bytes());
+
 		std::fs::write(&temp, data)?;
 		Self::run_command(&temp)
 	}
@@ -0,0 +1,23 @@
+(module
+	(type $none (func))
+	(type $one (func (result i32)))
+
+	(memory 1)
+	(table 2 funcref)
+	(elem (i32.const 0) $nothing)
+
+	(func $nothing)
+
+	(func (export "unreachable") unreachable)
+	(func (export "divide") (param i32) (result i32) (i32.div_u (i32.const 1) (local.get 0)))
+	(func (export "overflow") (param i32) (result i32) (i32.div_s (i32.const 0x80000000) (local.get 0)))
+	(func (export "load") (param i32) (result i32) (i32.load (local.get 0)))
+	(func (export "table") (param i32) (result funcref) (table.get (local.get 0)))
+	(func (export "call") (param i32) (result i32) (call_indirect (type $one) (local.get 0)))
+)
+
+(assert_trap (invoke "unreachable") "unreachable")
+(assert_trap (invoke "divide" (i32.const 0)) "integer divide by zero")
+(assert_trap (invoke "overflow" (i32.const -1)) "integer overflow")
+(assert_trap (invoke "load" (i32.const 65536)) "out of bounds memory access")
+(assert_trap (invoke "table" (i32.const 2)) "out of bounds table access")
@@ -0,0 +1,22 @@
+(module
+	(memory 1)
+	(data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c")
+
+	(func (export "load_i32") (param i32) (result i32) (i32.load offset=1 align=4 (local.get 0)))
+	(func (export "load_i64") (param i32) (result i64) (i64.load offset=2 align=8 (local.get 0)))
+
+	(func (export "store_i32") (param i32 i32) (i32.store offset=1 align=4 (local.get 0) (local.get 1)))
+	(func (export "store_i64") (param i32 i64) (i64.store offset=2 align=8 (local.get 0) (local.get 1)))
+)
+
+(assert_return (invoke "load_i32" (i32.const 0)) (i32.const 0x05040302))
+(assert_return (invoke "load_i32" (i32.const 2)) (i32.const 0x07060504))
+(assert_return (invoke "load_i64" (i32.const 1)) (i64.const 0x0b0a090807060504))
+
+(invoke "store_i32" (i32.const 2) (i32.const 0x11223344))
+(assert_return (invoke "load_i32" (i32.const 2)) (i32.const 0x11223344))
+(assert_return (invoke "load_i32" (i32.const 0)) (i32.const 0x33440302))
+
+(invoke "store_i64" (i32.const 3) (i64.const 0x0102030405060708))
+(assert_return (invoke "load_i64" (i32.const 3)) (i64.const 0x0102030405060708))
+(assert_return (invoke "load_i32" (i32.const 4)) (i32.const 0x05060708))
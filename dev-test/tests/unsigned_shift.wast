@@ -0,0 +1,41 @@
+(module
+	(func (export "shl_i32") (param i32 i32) (result i32) (i32.shl (local.get 0) (local.get 1)))
+	(func (export "shr_s_i32") (param i32 i32) (result i32) (i32.shr_s (local.get 0) (local.get 1)))
+	(func (export "shr_u_i32") (param i32 i32) (result i32) (i32.shr_u (local.get 0) (local.get 1)))
+	(func (export "rotl_i32") (param i32 i32) (result i32) (i32.rotl (local.get 0) (local.get 1)))
+	(func (export "rotr_i32") (param i32 i32) (result i32) (i32.rotr (local.get 0) (local.get 1)))
+	(func (export "lt_u_i32") (param i32 i32) (result i32) (i32.lt_u (local.get 0) (local.get 1)))
+	(func (export "ge_u_i32") (param i32 i32) (result i32) (i32.ge_u (local.get 0) (local.get 1)))
+
+	(func (export "shl_i64") (param i64 i64) (result i64) (i64.shl (local.get 0) (local.get 1)))
+	(func (export "shr_s_i64") (param i64 i64) (result i64) (i64.shr_s (local.get 0) (local.get 1)))
+	(func (export "shr_u_i64") (param i64 i64) (result i64) (i64.shr_u (local.get 0) (local.get 1)))
+	(func (export "rotl_i64") (param i64 i64) (result i64) (i64.rotl (local.get 0) (local.get 1)))
+	(func (export "rotr_i64") (param i64 i64) (result i64) (i64.rotr (local.get 0) (local.get 1)))
+	(func (export "lt_u_i64") (param i64 i64) (result i32) (i64.lt_u (local.get 0) (local.get 1)))
+	(func (export "ge_u_i64") (param i64 i64) (result i32) (i64.ge_u (local.get 0) (local.get 1)))
+)
+
+(assert_return (invoke "shr_u_i32" (i32.const -16) (i32.const 2)) (i32.const 0x3FFFFFFC))
+(assert_return (invoke "shr_u_i32" (i32.const -16) (i32.const 34)) (i32.const 0x3FFFFFFC))
+(assert_return (invoke "shr_s_i32" (i32.const -16) (i32.const 34)) (i32.const -4))
+(assert_return (invoke "shl_i32" (i32.const 3) (i32.const 33)) (i32.const 6))
+(assert_return (invoke "shl_i32" (i32.const 1) (i32.const 32)) (i32.const 1))
+(assert_return (invoke "shl_i32" (i32.const 1) (i32.const -1)) (i32.const 0x80000000))
+(assert_return (invoke "rotl_i32" (i32.const 0x80000001) (i32.const 1)) (i32.const 3))
+(assert_return (invoke "rotl_i32" (i32.const 0x80000001) (i32.const 33)) (i32.const 3))
+(assert_return (invoke "rotr_i32" (i32.const 1) (i32.const 1)) (i32.const 0x80000000))
+(assert_return (invoke "rotr_i32" (i32.const 1) (i32.const 0)) (i32.const 1))
+(assert_return (invoke "lt_u_i32" (i32.const -1) (i32.const 1)) (i32.const 0))
+(assert_return (invoke "ge_u_i32" (i32.const -1) (i32.const 1)) (i32.const 1))
+
+(assert_return (invoke "shr_u_i64" (i64.const -16) (i64.const 2)) (i64.const 0x3FFFFFFFFFFFFFFC))
+(assert_return (invoke "shr_u_i64" (i64.const -16) (i64.const 66)) (i64.const 0x3FFFFFFFFFFFFFFC))
+(assert_return (invoke "shr_s_i64" (i64.const -16) (i64.const 66)) (i64.const -4))
+(assert_return (invoke "shl_i64" (i64.const 3) (i64.const 65)) (i64.const 6))
+(assert_return (invoke "shl_i64" (i64.const 1) (i64.const 64)) (i64.const 1))
+(assert_return (invoke "rotl_i64" (i64.const 0x8000000000000001) (i64.const 1)) (i64.const 3))
+(assert_return (invoke "rotl_i64" (i64.const 0x8000000000000001) (i64.const 65)) (i64.const 3))
+(assert_return (invoke "rotr_i64" (i64.const 1) (i64.const 1)) (i64.const 0x8000000000000000))
+(assert_return (invoke "lt_u_i64" (i64.const -1) (i64.const 1)) (i32.const 0))
+(assert_return (invoke "ge_u_i64" (i64.const 0x8000000000000000) (i64.const 1)) (i32.const 1))
@@ -0,0 +1,71 @@
+use std::fmt::{Display, Formatter};
+
+use wasmparser::BinaryReaderError;
+
+#[derive(Debug)]
+pub enum Error {
+	Reader(BinaryReaderError),
+	BranchArity {
+		offset: usize,
+		expected: usize,
+		found: usize,
+	},
+	MissingCode {
+		expected: usize,
+		found: usize,
+	},
+	MissingDataCount {
+		offset: usize,
+	},
+	DataIndex {
+		offset: usize,
+		index: usize,
+	},
+	LocalIndex {
+		offset: usize,
+		index: usize,
+	},
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Reader(error) => error.fmt(f),
+			Self::BranchArity {
+				offset,
+				expected,
+				found,
+			} => write!(
+				f,
+				"branch expects {expected} values but found {found} (at offset 0x{offset:x})"
+			),
+			Self::MissingCode { expected, found } => {
+				write!(
+					f,
+					"module declares {expected} functions but has {found} bodies"
+				)
+			}
+			Self::MissingDataCount { offset } => write!(
+				f,
+				"data segment used without a data count (at offset 0x{offset:x})"
+			),
+			Self::DataIndex { offset, index } => write!(
+				f,
+				"data segment {index} does not exist (at offset 0x{offset:x})"
+			),
+			Self::LocalIndex { offset, index } => {
+				write!(f, "local {index} is not declared (at offset 0x{offset:x})")
+			}
+		}
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<BinaryReaderError> for Error {
+	fn from(error: BinaryReaderError) -> Self {
+		Self::Reader(error)
+	}
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
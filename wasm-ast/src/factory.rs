@@ -1,14 +1,16 @@
-use wasmparser::{BlockType, FunctionBody, MemArg, Operator, Result};
+use wasmparser::{BlockType, FunctionBody, MemArg, Operator};
 
 use crate::{
+	error::{Error, Result},
 	module::{read_checked, read_checked_locals, TypeInfo},
 	node::{
-		BinOp, BinOpType, Block, Br, BrIf, BrTable, Call, CallIndirect, CmpOp, CmpOpType,
-		Expression, FuncData, GetGlobal, If, LabelType, LoadAt, LoadType, Local, MemoryArgument,
-		MemoryCopy, MemoryFill, MemoryGrow, MemorySize, Select, SetGlobal, SetLocal, Statement,
-		StoreAt, StoreType, Terminator, UnOp, UnOpType, Value,
+		AtomicCmpxchg, AtomicRmw, BinOp, BinOpType, Block, Br, BrIf, BrTable, Call, CallIndirect,
+		CmpOp, CmpOpType, DataDrop, Expression, FuncData, GetGlobal, If, LabelType, LoadAt,
+		LoadType, Local, MemoryArgument, MemoryCopy, MemoryFill, MemoryGrow, MemoryInit,
+		MemorySize, RefFunc, ResultList, Select, SetGlobal, SetLocal, Statement, StoreAt,
+		StoreType, TableGet, TableGrow, TableSet, TableSize, Terminator, UnOp, UnOpType, Value,
 	},
-	stack::{ReadGet, Stack},
+	stack::{MayTrap, ReadGet, Stack},
 };
 
 #[derive(Clone, Copy)]
@@ -43,13 +45,197 @@ impl From<BlockData> for LabelType {
 	}
 }
 
+// Read-modify-write atomics act on the width of a plain load, with `None` standing
+// for the exchange that keeps no part of the old value.
+fn atomic_rmw_of(op: &Operator) -> Option<(Option<BinOpType>, LoadType, MemArg)> {
+	let result = match *op {
+		Operator::I32AtomicRmwAdd { memarg } => (Some(BinOpType::Add_I32), LoadType::I32, memarg),
+		Operator::I64AtomicRmwAdd { memarg } => (Some(BinOpType::Add_I64), LoadType::I64, memarg),
+		Operator::I32AtomicRmw8AddU { memarg } => {
+			(Some(BinOpType::Add_I32), LoadType::I32_U8, memarg)
+		}
+		Operator::I32AtomicRmw16AddU { memarg } => {
+			(Some(BinOpType::Add_I32), LoadType::I32_U16, memarg)
+		}
+		Operator::I64AtomicRmw8AddU { memarg } => {
+			(Some(BinOpType::Add_I64), LoadType::I64_U8, memarg)
+		}
+		Operator::I64AtomicRmw16AddU { memarg } => {
+			(Some(BinOpType::Add_I64), LoadType::I64_U16, memarg)
+		}
+		Operator::I64AtomicRmw32AddU { memarg } => {
+			(Some(BinOpType::Add_I64), LoadType::I64_U32, memarg)
+		}
+		Operator::I32AtomicRmwSub { memarg } => (Some(BinOpType::Sub_I32), LoadType::I32, memarg),
+		Operator::I64AtomicRmwSub { memarg } => (Some(BinOpType::Sub_I64), LoadType::I64, memarg),
+		Operator::I32AtomicRmw8SubU { memarg } => {
+			(Some(BinOpType::Sub_I32), LoadType::I32_U8, memarg)
+		}
+		Operator::I32AtomicRmw16SubU { memarg } => {
+			(Some(BinOpType::Sub_I32), LoadType::I32_U16, memarg)
+		}
+		Operator::I64AtomicRmw8SubU { memarg } => {
+			(Some(BinOpType::Sub_I64), LoadType::I64_U8, memarg)
+		}
+		Operator::I64AtomicRmw16SubU { memarg } => {
+			(Some(BinOpType::Sub_I64), LoadType::I64_U16, memarg)
+		}
+		Operator::I64AtomicRmw32SubU { memarg } => {
+			(Some(BinOpType::Sub_I64), LoadType::I64_U32, memarg)
+		}
+		Operator::I32AtomicRmwAnd { memarg } => (Some(BinOpType::And_I32), LoadType::I32, memarg),
+		Operator::I64AtomicRmwAnd { memarg } => (Some(BinOpType::And_I64), LoadType::I64, memarg),
+		Operator::I32AtomicRmw8AndU { memarg } => {
+			(Some(BinOpType::And_I32), LoadType::I32_U8, memarg)
+		}
+		Operator::I32AtomicRmw16AndU { memarg } => {
+			(Some(BinOpType::And_I32), LoadType::I32_U16, memarg)
+		}
+		Operator::I64AtomicRmw8AndU { memarg } => {
+			(Some(BinOpType::And_I64), LoadType::I64_U8, memarg)
+		}
+		Operator::I64AtomicRmw16AndU { memarg } => {
+			(Some(BinOpType::And_I64), LoadType::I64_U16, memarg)
+		}
+		Operator::I64AtomicRmw32AndU { memarg } => {
+			(Some(BinOpType::And_I64), LoadType::I64_U32, memarg)
+		}
+		Operator::I32AtomicRmwOr { memarg } => (Some(BinOpType::Or_I32), LoadType::I32, memarg),
+		Operator::I64AtomicRmwOr { memarg } => (Some(BinOpType::Or_I64), LoadType::I64, memarg),
+		Operator::I32AtomicRmw8OrU { memarg } => {
+			(Some(BinOpType::Or_I32), LoadType::I32_U8, memarg)
+		}
+		Operator::I32AtomicRmw16OrU { memarg } => {
+			(Some(BinOpType::Or_I32), LoadType::I32_U16, memarg)
+		}
+		Operator::I64AtomicRmw8OrU { memarg } => {
+			(Some(BinOpType::Or_I64), LoadType::I64_U8, memarg)
+		}
+		Operator::I64AtomicRmw16OrU { memarg } => {
+			(Some(BinOpType::Or_I64), LoadType::I64_U16, memarg)
+		}
+		Operator::I64AtomicRmw32OrU { memarg } => {
+			(Some(BinOpType::Or_I64), LoadType::I64_U32, memarg)
+		}
+		Operator::I32AtomicRmwXor { memarg } => (Some(BinOpType::Xor_I32), LoadType::I32, memarg),
+		Operator::I64AtomicRmwXor { memarg } => (Some(BinOpType::Xor_I64), LoadType::I64, memarg),
+		Operator::I32AtomicRmw8XorU { memarg } => {
+			(Some(BinOpType::Xor_I32), LoadType::I32_U8, memarg)
+		}
+		Operator::I32AtomicRmw16XorU { memarg } => {
+			(Some(BinOpType::Xor_I32), LoadType::I32_U16, memarg)
+		}
+		Operator::I64AtomicRmw8XorU { memarg } => {
+			(Some(BinOpType::Xor_I64), LoadType::I64_U8, memarg)
+		}
+		Operator::I64AtomicRmw16XorU { memarg } => {
+			(Some(BinOpType::Xor_I64), LoadType::I64_U16, memarg)
+		}
+		Operator::I64AtomicRmw32XorU { memarg } => {
+			(Some(BinOpType::Xor_I64), LoadType::I64_U32, memarg)
+		}
+		Operator::I32AtomicRmwXchg { memarg } => (None, LoadType::I32, memarg),
+		Operator::I64AtomicRmwXchg { memarg } => (None, LoadType::I64, memarg),
+		Operator::I32AtomicRmw8XchgU { memarg } => (None, LoadType::I32_U8, memarg),
+		Operator::I32AtomicRmw16XchgU { memarg } => (None, LoadType::I32_U16, memarg),
+		Operator::I64AtomicRmw8XchgU { memarg } => (None, LoadType::I64_U8, memarg),
+		Operator::I64AtomicRmw16XchgU { memarg } => (None, LoadType::I64_U16, memarg),
+		Operator::I64AtomicRmw32XchgU { memarg } => (None, LoadType::I64_U32, memarg),
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+fn atomic_cmpxchg_of(op: &Operator) -> Option<(LoadType, MemArg)> {
+	let result = match *op {
+		Operator::I32AtomicRmwCmpxchg { memarg } => (LoadType::I32, memarg),
+		Operator::I64AtomicRmwCmpxchg { memarg } => (LoadType::I64, memarg),
+		Operator::I32AtomicRmw8CmpxchgU { memarg } => (LoadType::I32_U8, memarg),
+		Operator::I32AtomicRmw16CmpxchgU { memarg } => (LoadType::I32_U16, memarg),
+		Operator::I64AtomicRmw8CmpxchgU { memarg } => (LoadType::I64_U8, memarg),
+		Operator::I64AtomicRmw16CmpxchgU { memarg } => (LoadType::I64_U16, memarg),
+		Operator::I64AtomicRmw32CmpxchgU { memarg } => (LoadType::I64_U32, memarg),
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// Folds follow WebAssembly semantics, wrapping on overflow and masking shift counts.
+// Operations that would trap are left to run so that they still trap.
+fn fold_i32(op_type: BinOpType, lhs: i32, rhs: i32) -> Option<i32> {
+	let (lhs_u, rhs_u) = (lhs as u32, rhs as u32);
+	let result = match op_type {
+		BinOpType::Add_I32 => lhs.wrapping_add(rhs),
+		BinOpType::Sub_I32 => lhs.wrapping_sub(rhs),
+		BinOpType::Mul_I32 => lhs.wrapping_mul(rhs),
+		BinOpType::DivS_I32 => lhs.checked_div(rhs)?,
+		BinOpType::DivU_I32 => lhs_u.checked_div(rhs_u)? as i32,
+		BinOpType::RemS_I32 if rhs != 0 => lhs.wrapping_rem(rhs),
+		BinOpType::RemU_I32 => lhs_u.checked_rem(rhs_u)? as i32,
+		BinOpType::And_I32 => lhs & rhs,
+		BinOpType::Or_I32 => lhs | rhs,
+		BinOpType::Xor_I32 => lhs ^ rhs,
+		BinOpType::Shl_I32 => lhs.wrapping_shl(rhs_u),
+		BinOpType::ShrS_I32 => lhs.wrapping_shr(rhs_u),
+		BinOpType::ShrU_I32 => lhs_u.wrapping_shr(rhs_u) as i32,
+		BinOpType::Rotl_I32 => lhs.rotate_left(rhs_u),
+		BinOpType::Rotr_I32 => lhs.rotate_right(rhs_u),
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+fn fold_i64(op_type: BinOpType, lhs: i64, rhs: i64) -> Option<i64> {
+	let (lhs_u, rhs_u) = (lhs as u64, rhs as u64);
+	let shift = rhs as u32;
+	let result = match op_type {
+		BinOpType::Add_I64 => lhs.wrapping_add(rhs),
+		BinOpType::Sub_I64 => lhs.wrapping_sub(rhs),
+		BinOpType::Mul_I64 => lhs.wrapping_mul(rhs),
+		BinOpType::DivS_I64 => lhs.checked_div(rhs)?,
+		BinOpType::DivU_I64 => lhs_u.checked_div(rhs_u)? as i64,
+		BinOpType::RemS_I64 if rhs != 0 => lhs.wrapping_rem(rhs),
+		BinOpType::RemU_I64 => lhs_u.checked_rem(rhs_u)? as i64,
+		BinOpType::And_I64 => lhs & rhs,
+		BinOpType::Or_I64 => lhs | rhs,
+		BinOpType::Xor_I64 => lhs ^ rhs,
+		BinOpType::Shl_I64 => lhs.wrapping_shl(shift),
+		BinOpType::ShrS_I64 => lhs.wrapping_shr(shift),
+		BinOpType::ShrU_I64 => lhs_u.wrapping_shr(shift) as i64,
+		BinOpType::Rotl_I64 => lhs.rotate_left(shift),
+		BinOpType::Rotr_I64 => lhs.rotate_right(shift),
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// Float operations are never folded, as their results depend on rounding and
+// `NaN` payloads that the targets do not reproduce exactly.
+fn fold_bin_op(op_type: BinOpType, lhs: &Expression, rhs: &Expression) -> Option<Value> {
+	match (lhs, rhs) {
+		(Expression::Value(Value::I32(lhs)), Expression::Value(Value::I32(rhs))) => {
+			fold_i32(op_type, *lhs, *rhs).map(Value::I32)
+		}
+		(Expression::Value(Value::I64(lhs)), Expression::Value(Value::I64(rhs))) => {
+			fold_i64(op_type, *lhs, *rhs).map(Value::I64)
+		}
+		_ => None,
+	}
+}
+
 #[derive(Default)]
 struct StatList {
 	stack: Stack,
 	code: Vec<Statement>,
+	offset_list: Vec<usize>,
 	last: Option<Box<Terminator>>,
 
 	block_data: BlockData,
+	block_offset: usize,
 	has_reference: bool,
 }
 
@@ -62,27 +248,46 @@ impl StatList {
 		self.stack.leak_into(&mut self.code, |_| true);
 	}
 
+	// Attribute every statement added since the last call to the instruction at `offset`
+	fn fill_offsets(&mut self, offset: usize) {
+		self.offset_list.resize(self.code.len(), offset);
+	}
+
 	fn leak_pre_call(&mut self) {
 		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |_| false, |_| true, |_| true)
+			ReadGet::run(node, |_| false, |_| true, |_| true, |_| true)
 		});
 	}
 
 	fn leak_local_write(&mut self, id: usize) {
 		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |var| var.var() == id, |_| false, |_| false)
+			ReadGet::run(node, |var| var.var() == id, |_| false, |_| false, |_| false)
 		});
 	}
 
 	fn leak_global_write(&mut self, id: usize) {
 		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |_| false, |var| var.var() == id, |_| false)
+			ReadGet::run(node, |_| false, |var| var.var() == id, |_| false, |_| false)
 		});
 	}
 
 	fn leak_memory_write(&mut self, id: usize) {
 		self.stack.leak_into(&mut self.code, |node| {
-			ReadGet::run(node, |_| false, |_| false, |var| var.memory() == id)
+			ReadGet::run(node, |_| false, |_| false, |var| var == id, |_| false)
+		});
+	}
+
+	// A dropped value that may trap is computed into its temporary first, along
+	// with those below it so that their traps keep happening in order.
+	fn leak_drop(&mut self) {
+		if self.stack.peek().is_some_and(MayTrap::run) {
+			self.stack.leak_into(&mut self.code, MayTrap::run);
+		}
+	}
+
+	fn leak_table_write(&mut self, id: usize) {
+		self.stack.leak_into(&mut self.code, |node| {
+			ReadGet::run(node, |_| false, |_| false, |_| false, |var| var == id)
 		});
 	}
 
@@ -94,6 +299,7 @@ impl StatList {
 			load_type,
 			memory,
 			offset,
+			align: memarg.align,
 			pointer: self.stack.pop().into(),
 		});
 
@@ -108,6 +314,7 @@ impl StatList {
 			store_type,
 			memory,
 			offset,
+			align: memarg.align,
 			value: self.stack.pop().into(),
 			pointer: self.stack.pop().into(),
 		});
@@ -116,6 +323,60 @@ impl StatList {
 		self.code.push(data);
 	}
 
+	fn add_atomic_rmw(&mut self, op_type: Option<BinOpType>, load_type: LoadType, memarg: MemArg) {
+		let memory = memarg.memory.try_into().unwrap();
+		let offset = memarg.offset.try_into().unwrap();
+		let value = self.stack.pop().into();
+		let pointer = self.stack.pop().into();
+		let result = self.stack.push_temporary();
+
+		let data = Statement::AtomicRmw(AtomicRmw {
+			op_type,
+			load_type,
+			memory,
+			offset,
+			result,
+			pointer,
+			value,
+		});
+
+		self.leak_memory_write(memory);
+		self.code.push(data);
+	}
+
+	fn add_atomic_cmpxchg(&mut self, load_type: LoadType, memarg: MemArg) {
+		let memory = memarg.memory.try_into().unwrap();
+		let offset = memarg.offset.try_into().unwrap();
+		let replacement = self.stack.pop().into();
+		let expected = self.stack.pop().into();
+		let pointer = self.stack.pop().into();
+		let result = self.stack.push_temporary();
+
+		let data = Statement::AtomicCmpxchg(AtomicCmpxchg {
+			load_type,
+			memory,
+			offset,
+			result,
+			pointer,
+			expected,
+			replacement,
+		});
+
+		self.leak_memory_write(memory);
+		self.code.push(data);
+	}
+
+	fn push_select(&mut self, is_reference: bool) {
+		let data = Expression::Select(Select {
+			condition: self.stack.pop().into(),
+			on_false: self.stack.pop().into(),
+			on_true: self.stack.pop().into(),
+			is_reference,
+		});
+
+		self.stack.push(data);
+	}
+
 	fn push_constant<T: Into<Value>>(&mut self, value: T) {
 		let value = Expression::Value(value.into());
 
@@ -132,11 +393,17 @@ impl StatList {
 	}
 
 	fn push_bin_op(&mut self, op_type: BinOpType) {
-		let data = Expression::BinOp(BinOp {
-			op_type,
-			rhs: self.stack.pop().into(),
-			lhs: self.stack.pop().into(),
-		});
+		let rhs = self.stack.pop();
+		let lhs = self.stack.pop();
+
+		let data = match fold_bin_op(op_type, &lhs, &rhs) {
+			Some(value) => Expression::Value(value),
+			None => Expression::BinOp(BinOp {
+				op_type,
+				lhs: lhs.into(),
+				rhs: rhs.into(),
+			}),
+		};
 
 		self.stack.push(data);
 	}
@@ -151,26 +418,6 @@ impl StatList {
 		self.stack.push(data);
 	}
 
-	// Eqz is the only unary comparison so it's "emulated"
-	// using a constant operand
-	fn try_add_equal_zero(&mut self, op: &Operator) -> bool {
-		match op {
-			Operator::I32Eqz => {
-				self.push_constant(0_i32);
-				self.push_cmp_op(CmpOpType::Eq_I32);
-
-				true
-			}
-			Operator::I64Eqz => {
-				self.push_constant(0_i64);
-				self.push_cmp_op(CmpOpType::Eq_I64);
-
-				true
-			}
-			_ => false,
-		}
-	}
-
 	// Try to generate a simple operation
 	fn try_add_operation(&mut self, op: &Operator) -> bool {
 		if let Ok(op_type) = UnOpType::try_from(op) {
@@ -186,7 +433,7 @@ impl StatList {
 
 			true
 		} else {
-			self.try_add_equal_zero(op)
+			false
 		}
 	}
 
@@ -203,6 +450,7 @@ impl From<StatList> for Block {
 		Self {
 			label_type,
 			code: stat.code,
+			offset_list: stat.offset_list,
 			last: stat.last,
 		}
 	}
@@ -215,6 +463,8 @@ pub struct Factory<'a> {
 	target: StatList,
 
 	nested_unreachable: usize,
+	num_local: usize,
+	offset: usize,
 }
 
 impl<'a> Factory<'a> {
@@ -225,15 +475,25 @@ impl<'a> Factory<'a> {
 			pending: Vec::new(),
 			target: StatList::new(),
 			nested_unreachable: 0,
+			num_local: usize::MAX,
+			offset: 0,
 		}
 	}
 
+	/// # Panics
+	///
+	/// Panics if the code branches with too few values.
 	#[must_use]
 	pub fn create_anonymous(&mut self, list: &[Operator]) -> FuncData {
-		let data = self.build_stat_list(list, 1);
+		let offset_list = vec![self.offset; list.len()];
+		let data = self
+			.build_stat_list(list, &offset_list, 1)
+			.expect("anonymous code should not branch");
 
 		FuncData {
 			local_data: Vec::new(),
+			param_data: Vec::new(),
+			result_data: Vec::new(),
 			num_result: 1,
 			num_param: 0,
 			num_stack: data.stack.capacity,
@@ -245,14 +505,25 @@ impl<'a> Factory<'a> {
 	///
 	/// Returns an error if the function is malformed.
 	pub fn create_indexed(&mut self, index: usize, func: &FunctionBody) -> Result<FuncData> {
-		let code = read_checked(func.get_operators_reader()?)?;
+		let (code, offset_list): (Vec<_>, Vec<_>) =
+			read_checked(func.get_operators_reader()?.into_iter_with_offsets())?
+				.into_iter()
+				.unzip();
+
 		let local_data = read_checked_locals(func.get_locals_reader()?)?;
 
-		let (num_param, num_result) = self.type_info.by_func_index(index);
-		let data = self.build_stat_list(&code, num_result);
+		let (param_data, result_data) = self.type_info.signature_by_func_index(index);
+		let (num_param, num_result) = (param_data.len(), result_data.len());
+
+		self.num_local = num_param + local_data.len();
+		self.offset = func.range().start;
+
+		let data = self.build_stat_list(&code, &offset_list, num_result)?;
 
 		Ok(FuncData {
 			local_data,
+			param_data: param_data.to_vec(),
+			result_data: result_data.to_vec(),
 			num_result,
 			num_param,
 			num_stack: data.stack.capacity,
@@ -265,7 +536,9 @@ impl<'a> Factory<'a> {
 		let mut old = std::mem::take(&mut self.target);
 
 		old.leak_all();
+		old.fill_offsets(self.offset);
 
+		self.target.block_offset = self.offset;
 		self.target.block_data = match variant {
 			BlockVariant::Forward => BlockData::Forward { num_result },
 			BlockVariant::Backward => BlockData::Backward { num_param },
@@ -297,7 +570,11 @@ impl<'a> Factory<'a> {
 
 	fn end_block(&mut self) {
 		let old = self.pending.pop().unwrap();
-		let now = std::mem::replace(&mut self.target, old);
+		let mut now = std::mem::replace(&mut self.target, old);
+
+		now.fill_offsets(self.offset);
+
+		let block_offset = now.block_offset;
 
 		self.target.stack.capacity = now.stack.capacity;
 
@@ -320,6 +597,7 @@ impl<'a> Factory<'a> {
 		};
 
 		self.target.code.push(stat);
+		self.target.fill_offsets(block_offset);
 	}
 
 	fn get_relative_block(&mut self, index: usize) -> &mut StatList {
@@ -332,7 +610,33 @@ impl<'a> Factory<'a> {
 		}
 	}
 
-	fn get_br_terminator(&mut self, target: usize) -> Br {
+	fn get_local_index(&self, index: u32) -> Result<usize> {
+		let index = index.try_into().unwrap();
+		let offset = self.offset;
+
+		if index < self.num_local {
+			Ok(index)
+		} else {
+			Err(Error::LocalIndex { offset, index })
+		}
+	}
+
+	fn get_data_index(&self, index: u32) -> Result<usize> {
+		let index = index.try_into().unwrap();
+		let offset = self.offset;
+
+		match self.type_info.data_count() {
+			Some(count) if index < count => Ok(index),
+			Some(_) => Err(Error::DataIndex { offset, index }),
+			None => Err(Error::MissingDataCount { offset }),
+		}
+	}
+
+	// Branches must carry as many values as their target expects, otherwise
+	// the alignment would read slots belonging to a parent frame
+	fn get_br_terminator(&mut self, target: usize) -> Result<Br> {
+		let len = self.target.stack.len();
+		let offset = self.offset;
 		let block = self.get_relative_block(target);
 		let previous = block.stack.previous;
 		let result = match block.block_data {
@@ -342,11 +646,19 @@ impl<'a> Factory<'a> {
 			BlockData::Backward { num_param } => num_param,
 		};
 
+		if len < result {
+			return Err(Error::BranchArity {
+				offset,
+				expected: result,
+				found: len,
+			});
+		}
+
 		block.has_reference = true;
 
 		let align = self.target.stack.get_br_alignment(previous, result);
 
-		Br { target, align }
+		Ok(Br { target, align })
 	}
 
 	fn add_call(&mut self, function: usize) {
@@ -377,6 +689,7 @@ impl<'a> Factory<'a> {
 
 		let data = Statement::CallIndirect(CallIndirect {
 			table,
+			type_index: ty,
 			index,
 			param_list,
 			result_list,
@@ -385,6 +698,81 @@ impl<'a> Factory<'a> {
 		self.target.code.push(data);
 	}
 
+	// Tail calls hand their results straight back, so they end the block like `return`
+	fn set_return_call(&mut self, function: usize) {
+		let (num_param, _) = self.type_info.by_func_index(function);
+		let param_list = self.target.stack.pop_len(num_param).collect();
+
+		self.target.set_terminator(Terminator::ReturnCall(Call {
+			function,
+			param_list,
+			result_list: ResultList::new(0, 0),
+		}));
+
+		self.nested_unreachable += 1;
+	}
+
+	fn set_return_call_indirect(&mut self, ty: usize, table: usize) {
+		let (num_param, _) = self.type_info.by_type_index(ty);
+		let index = self.target.stack.pop().into();
+		let param_list = self.target.stack.pop_len(num_param).collect();
+
+		self.target
+			.set_terminator(Terminator::ReturnCallIndirect(CallIndirect {
+				table,
+				type_index: ty,
+				index,
+				param_list,
+				result_list: ResultList::new(0, 0),
+			}));
+
+		self.nested_unreachable += 1;
+	}
+
+	// Memory64 addresses and sizes arrive as `i64`, so they are converted into
+	// the plain numbers that memory is indexed with.
+	fn convert_address(&mut self, memory: u32, depth: usize) {
+		if self.type_info.is_memory64(memory.try_into().unwrap()) {
+			self.target
+				.stack
+				.wrap_un_op(depth, UnOpType::Convert_F64_U64);
+		}
+	}
+
+	// Sizes are returned as `i64` for memory64, and never exceed the `i32` range.
+	fn extend_size(&mut self, memory: u32) {
+		if self.type_info.is_memory64(memory.try_into().unwrap()) {
+			self.target.stack.wrap_un_op(0, UnOpType::Extend_I64_I32);
+		}
+	}
+
+	fn push_load(&mut self, load_type: LoadType, memarg: MemArg) {
+		self.convert_address(memarg.memory, 0);
+		self.target.push_load(load_type, memarg);
+	}
+
+	fn add_store(&mut self, store_type: StoreType, memarg: MemArg) {
+		self.convert_address(memarg.memory, 1);
+		self.target.add_store(store_type, memarg);
+	}
+
+	// Single threaded, atomics behave as their plain load and store would
+	fn try_add_atomic(&mut self, op: &Operator) -> bool {
+		if let Some((op_type, load_type, memarg)) = atomic_rmw_of(op) {
+			self.convert_address(memarg.memory, 1);
+			self.target.add_atomic_rmw(op_type, load_type, memarg);
+
+			true
+		} else if let Some((load_type, memarg)) = atomic_cmpxchg_of(op) {
+			self.convert_address(memarg.memory, 2);
+			self.target.add_atomic_cmpxchg(load_type, memarg);
+
+			true
+		} else {
+			false
+		}
+	}
+
 	#[cold]
 	fn drop_unreachable(&mut self, op: &Operator) {
 		match op {
@@ -409,9 +797,9 @@ impl<'a> Factory<'a> {
 	}
 
 	#[allow(clippy::too_many_lines)]
-	fn add_instruction(&mut self, op: &Operator) {
-		if self.target.try_add_operation(op) {
-			return;
+	fn add_instruction(&mut self, op: &Operator) -> Result<()> {
+		if self.target.try_add_operation(op) || self.try_add_atomic(op) {
+			return Ok(());
 		}
 
 		match *op {
@@ -442,7 +830,7 @@ impl<'a> Factory<'a> {
 			}
 			Operator::Br { relative_depth } => {
 				let target = relative_depth.try_into().unwrap();
-				let term = Terminator::Br(self.get_br_terminator(target));
+				let term = Terminator::Br(self.get_br_terminator(target)?);
 
 				self.target.set_terminator(term);
 				self.nested_unreachable += 1;
@@ -451,7 +839,7 @@ impl<'a> Factory<'a> {
 				let target = relative_depth.try_into().unwrap();
 				let data = Statement::BrIf(BrIf {
 					condition: self.target.stack.pop().into(),
-					target: self.get_br_terminator(target),
+					target: self.get_br_terminator(target)?,
 				});
 
 				self.target.leak_all();
@@ -461,11 +849,10 @@ impl<'a> Factory<'a> {
 				let condition = self.target.stack.pop().into();
 				let data = targets
 					.targets()
-					.map(Result::unwrap)
-					.map(|v| self.get_br_terminator(v.try_into().unwrap()))
-					.collect();
+					.map(|v| self.get_br_terminator(v?.try_into().unwrap()))
+					.collect::<Result<_>>()?;
 
-				let default = self.get_br_terminator(targets.default().try_into().unwrap());
+				let default = self.get_br_terminator(targets.default().try_into().unwrap())?;
 
 				let term = Terminator::BrTable(BrTable {
 					condition,
@@ -478,7 +865,7 @@ impl<'a> Factory<'a> {
 			}
 			Operator::Return => {
 				let target = self.pending.len();
-				let term = Terminator::Br(self.get_br_terminator(target));
+				let term = Terminator::Br(self.get_br_terminator(target)?);
 
 				self.target.set_terminator(term);
 				self.nested_unreachable += 1;
@@ -498,26 +885,34 @@ impl<'a> Factory<'a> {
 
 				self.add_call_indirect(type_index, table_index);
 			}
-			Operator::Drop => {
-				self.target.stack.pop();
+			Operator::ReturnCall { function_index } => {
+				let index = function_index.try_into().unwrap();
+
+				self.set_return_call(index);
 			}
-			Operator::Select => {
-				let data = Expression::Select(Select {
-					condition: self.target.stack.pop().into(),
-					on_false: self.target.stack.pop().into(),
-					on_true: self.target.stack.pop().into(),
-				});
+			Operator::ReturnCallIndirect {
+				type_index,
+				table_index,
+			} => {
+				let type_index = type_index.try_into().unwrap();
+				let table_index = table_index.try_into().unwrap();
 
-				self.target.stack.push(data);
+				self.set_return_call_indirect(type_index, table_index);
+			}
+			Operator::Drop => {
+				self.target.leak_drop();
+				self.target.stack.pop();
 			}
+			Operator::Select => self.target.push_select(false),
+			Operator::TypedSelect { ty } => self.target.push_select(ty.is_reference_type()),
 			Operator::LocalGet { local_index } => {
-				let var = local_index.try_into().unwrap();
+				let var = self.get_local_index(local_index)?;
 				let data = Expression::GetLocal(Local { var });
 
 				self.target.stack.push(data);
 			}
 			Operator::LocalSet { local_index } => {
-				let var = local_index.try_into().unwrap();
+				let var = self.get_local_index(local_index)?;
 				let data = Statement::SetLocal(SetLocal {
 					var: Local { var },
 					value: self.target.stack.pop().into(),
@@ -527,7 +922,7 @@ impl<'a> Factory<'a> {
 				self.target.code.push(data);
 			}
 			Operator::LocalTee { local_index } => {
-				let var = local_index.try_into().unwrap();
+				let var = self.get_local_index(local_index)?;
 				let get = Expression::GetLocal(Local { var });
 				let set = Statement::SetLocal(SetLocal {
 					var: Local { var },
@@ -554,36 +949,63 @@ impl<'a> Factory<'a> {
 				self.target.leak_global_write(var);
 				self.target.code.push(data);
 			}
-			Operator::I32Load { memarg } => self.target.push_load(LoadType::I32, memarg),
-			Operator::I64Load { memarg } => self.target.push_load(LoadType::I64, memarg),
-			Operator::F32Load { memarg } => self.target.push_load(LoadType::F32, memarg),
-			Operator::F64Load { memarg } => self.target.push_load(LoadType::F64, memarg),
-			Operator::I32Load8S { memarg } => self.target.push_load(LoadType::I32_I8, memarg),
-			Operator::I32Load8U { memarg } => self.target.push_load(LoadType::I32_U8, memarg),
-			Operator::I32Load16S { memarg } => self.target.push_load(LoadType::I32_I16, memarg),
-			Operator::I32Load16U { memarg } => self.target.push_load(LoadType::I32_U16, memarg),
-			Operator::I64Load8S { memarg } => self.target.push_load(LoadType::I64_I8, memarg),
-			Operator::I64Load8U { memarg } => self.target.push_load(LoadType::I64_U8, memarg),
-			Operator::I64Load16S { memarg } => self.target.push_load(LoadType::I64_I16, memarg),
-			Operator::I64Load16U { memarg } => self.target.push_load(LoadType::I64_U16, memarg),
-			Operator::I64Load32S { memarg } => self.target.push_load(LoadType::I64_I32, memarg),
-			Operator::I64Load32U { memarg } => self.target.push_load(LoadType::I64_U32, memarg),
-			Operator::I32Store { memarg } => self.target.add_store(StoreType::I32, memarg),
-			Operator::I64Store { memarg } => self.target.add_store(StoreType::I64, memarg),
-			Operator::F32Store { memarg } => self.target.add_store(StoreType::F32, memarg),
-			Operator::F64Store { memarg } => self.target.add_store(StoreType::F64, memarg),
-			Operator::I32Store8 { memarg } => self.target.add_store(StoreType::I32_N8, memarg),
-			Operator::I32Store16 { memarg } => self.target.add_store(StoreType::I32_N16, memarg),
-			Operator::I64Store8 { memarg } => self.target.add_store(StoreType::I64_N8, memarg),
-			Operator::I64Store16 { memarg } => self.target.add_store(StoreType::I64_N16, memarg),
-			Operator::I64Store32 { memarg } => self.target.add_store(StoreType::I64_N32, memarg),
+			// Whether the function was declared by an element segment is left to validation.
+			Operator::RefFunc { function_index } => {
+				let function = function_index.try_into().unwrap();
+				let data = Expression::RefFunc(RefFunc { function });
+
+				self.target.stack.push(data);
+			}
+			Operator::RefNull { .. } => self.target.stack.push(Expression::RefNull),
+			Operator::I32Load { memarg } => self.push_load(LoadType::I32, memarg),
+			Operator::I64Load { memarg } => self.push_load(LoadType::I64, memarg),
+			Operator::F32Load { memarg } => self.push_load(LoadType::F32, memarg),
+			Operator::F64Load { memarg } => self.push_load(LoadType::F64, memarg),
+			Operator::I32Load8S { memarg } => self.push_load(LoadType::I32_I8, memarg),
+			Operator::I32Load8U { memarg } => self.push_load(LoadType::I32_U8, memarg),
+			Operator::I32Load16S { memarg } => self.push_load(LoadType::I32_I16, memarg),
+			Operator::I32Load16U { memarg } => self.push_load(LoadType::I32_U16, memarg),
+			Operator::I64Load8S { memarg } => self.push_load(LoadType::I64_I8, memarg),
+			Operator::I64Load8U { memarg } => self.push_load(LoadType::I64_U8, memarg),
+			Operator::I64Load16S { memarg } => self.push_load(LoadType::I64_I16, memarg),
+			Operator::I64Load16U { memarg } => self.push_load(LoadType::I64_U16, memarg),
+			Operator::I64Load32S { memarg } => self.push_load(LoadType::I64_I32, memarg),
+			Operator::I64Load32U { memarg } => self.push_load(LoadType::I64_U32, memarg),
+			Operator::I32Store { memarg } => self.add_store(StoreType::I32, memarg),
+			Operator::I64Store { memarg } => self.add_store(StoreType::I64, memarg),
+			Operator::F32Store { memarg } => self.add_store(StoreType::F32, memarg),
+			Operator::F64Store { memarg } => self.add_store(StoreType::F64, memarg),
+			Operator::I32Store8 { memarg } => self.add_store(StoreType::I32_N8, memarg),
+			Operator::I32Store16 { memarg } => self.add_store(StoreType::I32_N16, memarg),
+			Operator::I64Store8 { memarg } => self.add_store(StoreType::I64_N8, memarg),
+			Operator::I64Store16 { memarg } => self.add_store(StoreType::I64_N16, memarg),
+			Operator::I64Store32 { memarg } => self.add_store(StoreType::I64_N32, memarg),
+			Operator::I32AtomicLoad { memarg } => self.push_load(LoadType::I32, memarg),
+			Operator::I64AtomicLoad { memarg } => self.push_load(LoadType::I64, memarg),
+			Operator::I32AtomicLoad8U { memarg } => self.push_load(LoadType::I32_U8, memarg),
+			Operator::I32AtomicLoad16U { memarg } => self.push_load(LoadType::I32_U16, memarg),
+			Operator::I64AtomicLoad8U { memarg } => self.push_load(LoadType::I64_U8, memarg),
+			Operator::I64AtomicLoad16U { memarg } => self.push_load(LoadType::I64_U16, memarg),
+			Operator::I64AtomicLoad32U { memarg } => self.push_load(LoadType::I64_U32, memarg),
+			Operator::I32AtomicStore { memarg } => self.add_store(StoreType::I32, memarg),
+			Operator::I64AtomicStore { memarg } => self.add_store(StoreType::I64, memarg),
+			Operator::I32AtomicStore8 { memarg } => self.add_store(StoreType::I32_N8, memarg),
+			Operator::I32AtomicStore16 { memarg } => self.add_store(StoreType::I32_N16, memarg),
+			Operator::I64AtomicStore8 { memarg } => self.add_store(StoreType::I64_N8, memarg),
+			Operator::I64AtomicStore16 { memarg } => self.add_store(StoreType::I64_N16, memarg),
+			Operator::I64AtomicStore32 { memarg } => self.add_store(StoreType::I64_N32, memarg),
+			// A single thread has no other accesses to order against.
+			Operator::AtomicFence => {}
 			Operator::MemorySize { mem, .. } => {
 				let memory = mem.try_into().unwrap();
 				let data = Expression::MemorySize(MemorySize { memory });
 
 				self.target.stack.push(data);
+				self.extend_size(mem);
 			}
 			Operator::MemoryGrow { mem, .. } => {
+				self.convert_address(mem, 0);
+
 				let size = self.target.stack.pop().into();
 				let result = self.target.stack.push_temporary();
 				let memory = mem.try_into().unwrap();
@@ -596,8 +1018,59 @@ impl<'a> Factory<'a> {
 
 				self.target.leak_memory_write(memory);
 				self.target.code.push(data);
+				self.extend_size(mem);
+			}
+			Operator::TableGet { table } => {
+				let table = table.try_into().unwrap();
+				let data = Expression::TableGet(TableGet {
+					table,
+					index: self.target.stack.pop().into(),
+				});
+
+				self.target.stack.push(data);
+			}
+			Operator::TableSet { table } => {
+				let table = table.try_into().unwrap();
+				let data = Statement::TableSet(TableSet {
+					table,
+					value: self.target.stack.pop().into(),
+					index: self.target.stack.pop().into(),
+				});
+
+				self.target.leak_table_write(table);
+				self.target.code.push(data);
+			}
+			Operator::TableSize { table } => {
+				let table = table.try_into().unwrap();
+				let data = Expression::TableSize(TableSize { table });
+
+				self.target.stack.push(data);
+			}
+			Operator::TableGrow { table } => {
+				let size = self.target.stack.pop().into();
+				let value = self.target.stack.pop().into();
+				let result = self.target.stack.push_temporary();
+				let table = table.try_into().unwrap();
+
+				let data = Statement::TableGrow(TableGrow {
+					table,
+					result,
+					value,
+					size,
+				});
+
+				self.target.leak_table_write(table);
+				self.target.code.push(data);
 			}
 			Operator::MemoryCopy { dst_mem, src_mem } => {
+				self.convert_address(dst_mem, 2);
+				self.convert_address(src_mem, 1);
+
+				// The size is only `i64` when both memories are.
+				if self.type_info.is_memory64(src_mem.try_into().unwrap()) {
+					self.convert_address(dst_mem, 0);
+				}
+
 				let size = self.target.stack.pop().into();
 
 				let source = MemoryArgument {
@@ -622,6 +1095,9 @@ impl<'a> Factory<'a> {
 				self.target.code.push(data);
 			}
 			Operator::MemoryFill { mem } => {
+				self.convert_address(mem, 2);
+				self.convert_address(mem, 0);
+
 				let size = self.target.stack.pop().into();
 				let value = self.target.stack.pop().into();
 
@@ -640,30 +1116,72 @@ impl<'a> Factory<'a> {
 
 				self.target.code.push(data);
 			}
+			Operator::MemoryInit { data_index, mem } => {
+				self.convert_address(mem, 2);
+
+				let data = self.get_data_index(data_index)?;
+				let size = self.target.stack.pop().into();
+				let offset = self.target.stack.pop().into();
+
+				let destination = MemoryArgument {
+					memory: mem.try_into().unwrap(),
+					pointer: self.target.stack.pop().into(),
+				};
+
+				self.target.leak_memory_write(destination.memory);
+
+				let data = Statement::MemoryInit(MemoryInit {
+					destination,
+					data,
+					offset,
+					size,
+				});
+
+				self.target.code.push(data);
+			}
+			Operator::DataDrop { data_index } => {
+				let data = Statement::DataDrop(DataDrop {
+					data: self.get_data_index(data_index)?,
+				});
+
+				self.target.code.push(data);
+			}
 			Operator::I32Const { value } => self.target.push_constant(value),
 			Operator::I64Const { value } => self.target.push_constant(value),
 			Operator::F32Const { value } => self.target.push_constant(value.bits()),
 			Operator::F64Const { value } => self.target.push_constant(value.bits()),
 			_ => panic!("Unsupported instruction: {op:?}"),
 		}
+
+		Ok(())
 	}
 
-	fn build_stat_list(&mut self, list: &[Operator], num_result: usize) -> StatList {
+	fn build_stat_list(
+		&mut self,
+		list: &[Operator],
+		offset_list: &[usize],
+		num_result: usize,
+	) -> Result<StatList> {
 		self.target.block_data = BlockData::Forward { num_result };
 		self.nested_unreachable = 0;
 
-		for op in list.iter().take(list.len() - 1) {
+		for (op, &offset) in list.iter().zip(offset_list).take(list.len() - 1) {
+			self.offset = offset;
+
 			if self.nested_unreachable == 0 {
-				self.add_instruction(op);
+				self.add_instruction(op)?;
 			} else {
 				self.drop_unreachable(op);
 			}
+
+			self.target.fill_offsets(offset);
 		}
 
 		if self.nested_unreachable == 0 {
 			self.target.leak_all();
+			self.target.fill_offsets(self.offset);
 		}
 
-		std::mem::take(&mut self.target)
+		Ok(std::mem::take(&mut self.target))
 	}
 }
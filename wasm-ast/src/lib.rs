@@ -1,3 +1,4 @@
+pub mod error;
 pub mod factory;
 pub mod module;
 pub mod node;
@@ -5,7 +5,9 @@ use wasmparser::{
 	MemoryType, Name, NameSectionReader, Parser, Payload, Result, Table, Type, TypeRef, ValType,
 };
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+use crate::error::Error;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub enum External {
 	Func,
 	Table,
@@ -68,13 +70,15 @@ pub struct Module<'a> {
 	name_section: HashMap<u32, &'a str>,
 
 	start_section: Option<u32>,
+	data_count_section: Option<u32>,
 }
 
 impl<'a> Module<'a> {
 	/// # Errors
 	///
-	/// Returns a `BinaryReaderError` if any module section is malformed.
-	pub fn try_from_data(data: &'a [u8]) -> Result<Self> {
+	/// Returns an `Error` if any module section is malformed or a declared
+	/// function has no body.
+	pub fn try_from_data(data: &'a [u8]) -> crate::error::Result<Self> {
 		let mut temp = Module {
 			type_section: Vec::new(),
 			import_section: Vec::new(),
@@ -88,9 +92,18 @@ impl<'a> Module<'a> {
 			code_section: Vec::new(),
 			name_section: HashMap::new(),
 			start_section: None,
+			data_count_section: None,
 		};
 
 		temp.load_data(data)?;
+
+		if temp.func_section.len() != temp.code_section.len() {
+			return Err(Error::MissingCode {
+				expected: temp.func_section.len(),
+				found: temp.code_section.len(),
+			});
+		}
+
 		Ok(temp)
 	}
 
@@ -112,6 +125,9 @@ impl<'a> Module<'a> {
 				Payload::StartSection { func, .. } => {
 					self.start_section = Some(func);
 				}
+				Payload::DataCountSection { count, .. } => {
+					self.data_count_section = Some(count);
+				}
 				Payload::CustomSection(v) if v.name() == "name" => {
 					for name in NameSectionReader::new(v.data(), v.data_offset()) {
 						if let Name::Function(map) = name? {
@@ -215,11 +231,18 @@ impl<'a> Module<'a> {
 	pub const fn start_section(&self) -> Option<u32> {
 		self.start_section
 	}
+
+	#[must_use]
+	pub const fn data_count_section(&self) -> Option<u32> {
+		self.data_count_section
+	}
 }
 
 pub struct TypeInfo<'a> {
 	type_list: &'a [Type],
 	func_list: Vec<usize>,
+	memory64_list: Vec<bool>,
+	data_count: Option<usize>,
 }
 
 impl<'a> TypeInfo<'a> {
@@ -228,10 +251,13 @@ impl<'a> TypeInfo<'a> {
 		let mut temp = Self {
 			type_list: &wasm.type_section,
 			func_list: Vec::new(),
+			memory64_list: Vec::new(),
+			data_count: wasm.data_count_section.map(|v| v.try_into().unwrap()),
 		};
 
 		temp.load_import_list(&wasm.import_section);
 		temp.load_func_list(&wasm.func_section);
+		temp.load_memory_list(&wasm.memory_section);
 		temp
 	}
 
@@ -246,6 +272,13 @@ impl<'a> TypeInfo<'a> {
 			.map(|v| usize::try_from(v).unwrap());
 
 		self.func_list.extend(iter);
+
+		let iter = list.iter().filter_map(|v| match v.ty {
+			TypeRef::Memory(v) => Some(v.memory64),
+			_ => None,
+		});
+
+		self.memory64_list.extend(iter);
 	}
 
 	fn load_func_list(&mut self, list: &[u32]) {
@@ -254,6 +287,44 @@ impl<'a> TypeInfo<'a> {
 		self.func_list.extend(iter);
 	}
 
+	fn load_memory_list(&mut self, list: &[MemoryType]) {
+		self.memory64_list.extend(list.iter().map(|v| v.memory64));
+	}
+
+	/// Returns the first type index declaring the same signature as `index`, so
+	/// that structurally equal types share one id.
+	#[must_use]
+	pub fn canonical_type_index(&self, index: usize) -> usize {
+		let Type::Func(ty) = &self.type_list[index] else {
+			return index;
+		};
+
+		let same = |v: &Type| {
+			let Type::Func(v) = v else { return false };
+
+			v.params() == ty.params() && v.results() == ty.results()
+		};
+
+		self.type_list.iter().position(same).unwrap()
+	}
+
+	/// Returns the type index of the function at `index` in the function space.
+	#[must_use]
+	pub fn func_type_index(&self, index: usize) -> usize {
+		self.func_list[index]
+	}
+
+	/// Returns whether the memory at `index` in the memory space is addressed
+	/// with `i64` values, as allowed by the memory64 proposal.
+	#[must_use]
+	pub fn is_memory64(&self, index: usize) -> bool {
+		self.memory64_list[index]
+	}
+
+	pub(crate) const fn data_count(&self) -> Option<usize> {
+		self.data_count
+	}
+
 	pub(crate) fn by_type_index(&self, index: usize) -> (usize, usize) {
 		let Type::Func(ty) = &self.type_list[index] else {
 			unreachable!("type at func index must be a func type");
@@ -262,6 +333,14 @@ impl<'a> TypeInfo<'a> {
 		(ty.params().len(), ty.results().len())
 	}
 
+	pub(crate) fn signature_by_func_index(&self, index: usize) -> (&[ValType], &[ValType]) {
+		let Type::Func(ty) = &self.type_list[self.func_list[index]] else {
+			unreachable!("type at func index must be a func type");
+		};
+
+		(ty.params(), ty.results())
+	}
+
 	pub(crate) fn by_func_index(&self, index: usize) -> (usize, usize) {
 		let adjusted = self.func_list[index];
 
@@ -80,11 +80,30 @@ impl TryFrom<&Operator<'_>> for StoreType {
 	}
 }
 
+// Every load has a store of the same width, which atomics use to write back.
+impl From<LoadType> for StoreType {
+	fn from(value: LoadType) -> Self {
+		match value {
+			LoadType::I32 => Self::I32,
+			LoadType::I64 => Self::I64,
+			LoadType::F32 => Self::F32,
+			LoadType::F64 => Self::F64,
+			LoadType::I32_I8 | LoadType::I32_U8 => Self::I32_N8,
+			LoadType::I32_I16 | LoadType::I32_U16 => Self::I32_N16,
+			LoadType::I64_I8 | LoadType::I64_U8 => Self::I64_N8,
+			LoadType::I64_I16 | LoadType::I64_U16 => Self::I64_N16,
+			LoadType::I64_I32 | LoadType::I64_U32 => Self::I64_N32,
+		}
+	}
+}
+
 // Order of mnemonics is:
 // operation_result_parameter
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy)]
 pub enum UnOpType {
+	Eqz_I32,
+	Eqz_I64,
 	Clz_I32,
 	Ctz_I32,
 	Popcnt_I32,
@@ -150,6 +169,8 @@ impl TryFrom<&Operator<'_>> for UnOpType {
 
 	fn try_from(inst: &Operator) -> Result<Self, Self::Error> {
 		let result = match inst {
+			Operator::I32Eqz => Self::Eqz_I32,
+			Operator::I64Eqz => Self::Eqz_I64,
 			Operator::I32Clz => Self::Clz_I32,
 			Operator::I32Ctz => Self::Ctz_I32,
 			Operator::I32Popcnt => Self::Popcnt_I32,
@@ -409,6 +430,7 @@ pub struct Select {
 	pub(crate) condition: Box<Expression>,
 	pub(crate) on_true: Box<Expression>,
 	pub(crate) on_false: Box<Expression>,
+	pub(crate) is_reference: bool,
 }
 
 impl Select {
@@ -426,6 +448,12 @@ impl Select {
 	pub const fn on_false(&self) -> &Expression {
 		&self.on_false
 	}
+
+	/// Returns whether the operands are references, which may be `nil`.
+	#[must_use]
+	pub const fn is_reference(&self) -> bool {
+		self.is_reference
+	}
 }
 
 #[derive(Clone, Copy)]
@@ -464,10 +492,23 @@ impl GetGlobal {
 	}
 }
 
+#[derive(Clone, Copy)]
+pub struct RefFunc {
+	pub(crate) function: usize,
+}
+
+impl RefFunc {
+	#[must_use]
+	pub const fn function(self) -> usize {
+		self.function
+	}
+}
+
 pub struct LoadAt {
 	pub(crate) load_type: LoadType,
 	pub(crate) memory: usize,
 	pub(crate) offset: u32,
+	pub(crate) align: u8,
 	pub(crate) pointer: Box<Expression>,
 }
 
@@ -487,6 +528,13 @@ impl LoadAt {
 		self.offset
 	}
 
+	/// Returns the alignment hint as a power of two. Accesses behave the same
+	/// at any address, so this only tells what the producer expected.
+	#[must_use]
+	pub const fn align(&self) -> u8 {
+		self.align
+	}
+
 	#[must_use]
 	pub const fn pointer(&self) -> &Expression {
 		&self.pointer
@@ -505,6 +553,35 @@ impl MemorySize {
 	}
 }
 
+pub struct TableGet {
+	pub(crate) table: usize,
+	pub(crate) index: Box<Expression>,
+}
+
+impl TableGet {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn index(&self) -> &Expression {
+		&self.index
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct TableSize {
+	pub(crate) table: usize,
+}
+
+impl TableSize {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+}
+
 #[derive(Clone, Copy)]
 pub enum Value {
 	I32(i32),
@@ -605,8 +682,12 @@ pub enum Expression {
 	GetTemporary(Temporary),
 	GetLocal(Local),
 	GetGlobal(GetGlobal),
+	RefFunc(RefFunc),
+	RefNull,
 	LoadAt(LoadAt),
 	MemorySize(MemorySize),
+	TableGet(TableGet),
+	TableSize(TableSize),
 	Value(Value),
 	UnOp(UnOp),
 	BinOp(BinOp),
@@ -710,12 +791,15 @@ pub enum Terminator {
 	Unreachable,
 	Br(Br),
 	BrTable(BrTable),
+	ReturnCall(Call),
+	ReturnCallIndirect(CallIndirect),
 }
 
 #[derive(Default)]
 pub struct Block {
 	pub(crate) label_type: Option<LabelType>,
 	pub(crate) code: Vec<Statement>,
+	pub(crate) offset_list: Vec<usize>,
 	pub(crate) last: Option<Box<Terminator>>,
 }
 
@@ -730,6 +814,12 @@ impl Block {
 		&self.code
 	}
 
+	/// Returns the byte offset of the instruction each statement of `code` came from.
+	#[must_use]
+	pub fn offset_list(&self) -> &[usize] {
+		&self.offset_list
+	}
+
 	#[must_use]
 	pub fn last(&self) -> Option<&Terminator> {
 		self.last.as_deref()
@@ -801,6 +891,7 @@ impl Call {
 
 pub struct CallIndirect {
 	pub(crate) table: usize,
+	pub(crate) type_index: usize,
 	pub(crate) index: Box<Expression>,
 	pub(crate) param_list: Vec<Expression>,
 	pub(crate) result_list: ResultList,
@@ -812,6 +903,11 @@ impl CallIndirect {
 		self.table
 	}
 
+	#[must_use]
+	pub const fn type_index(&self) -> usize {
+		self.type_index
+	}
+
 	#[must_use]
 	pub const fn index(&self) -> &Expression {
 		&self.index
@@ -883,6 +979,7 @@ pub struct StoreAt {
 	pub(crate) store_type: StoreType,
 	pub(crate) memory: usize,
 	pub(crate) offset: u32,
+	pub(crate) align: u8,
 	pub(crate) pointer: Box<Expression>,
 	pub(crate) value: Box<Expression>,
 }
@@ -903,6 +1000,13 @@ impl StoreAt {
 		self.offset
 	}
 
+	/// Returns the alignment hint as a power of two. Accesses behave the same
+	/// at any address, so this only tells what the producer expected.
+	#[must_use]
+	pub const fn align(&self) -> u8 {
+		self.align
+	}
+
 	#[must_use]
 	pub const fn pointer(&self) -> &Expression {
 		&self.pointer
@@ -937,6 +1041,164 @@ impl MemoryGrow {
 	}
 }
 
+pub struct TableSet {
+	pub(crate) table: usize,
+	pub(crate) index: Box<Expression>,
+	pub(crate) value: Box<Expression>,
+}
+
+impl TableSet {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn index(&self) -> &Expression {
+		&self.index
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+}
+
+pub struct TableGrow {
+	pub(crate) table: usize,
+	pub(crate) result: Temporary,
+	pub(crate) value: Box<Expression>,
+	pub(crate) size: Box<Expression>,
+}
+
+impl TableGrow {
+	#[must_use]
+	pub const fn table(&self) -> usize {
+		self.table
+	}
+
+	#[must_use]
+	pub const fn result(&self) -> Temporary {
+		self.result
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+}
+
+pub struct AtomicRmw {
+	pub(crate) op_type: Option<BinOpType>,
+	pub(crate) load_type: LoadType,
+	pub(crate) memory: usize,
+	pub(crate) offset: u32,
+	pub(crate) result: Temporary,
+	pub(crate) pointer: Box<Expression>,
+	pub(crate) value: Box<Expression>,
+}
+
+impl AtomicRmw {
+	/// Returns the operation combining the old and given values, or `None` when
+	/// the given value replaces the old one as is.
+	#[must_use]
+	pub const fn op_type(&self) -> Option<BinOpType> {
+		self.op_type
+	}
+
+	#[must_use]
+	pub const fn load_type(&self) -> LoadType {
+		self.load_type
+	}
+
+	#[must_use]
+	pub fn store_type(&self) -> StoreType {
+		self.load_type.into()
+	}
+
+	#[must_use]
+	pub const fn memory(&self) -> usize {
+		self.memory
+	}
+
+	#[must_use]
+	pub const fn offset(&self) -> u32 {
+		self.offset
+	}
+
+	#[must_use]
+	pub const fn result(&self) -> Temporary {
+		self.result
+	}
+
+	#[must_use]
+	pub const fn pointer(&self) -> &Expression {
+		&self.pointer
+	}
+
+	#[must_use]
+	pub const fn value(&self) -> &Expression {
+		&self.value
+	}
+}
+
+pub struct AtomicCmpxchg {
+	pub(crate) load_type: LoadType,
+	pub(crate) memory: usize,
+	pub(crate) offset: u32,
+	pub(crate) result: Temporary,
+	pub(crate) pointer: Box<Expression>,
+	pub(crate) expected: Box<Expression>,
+	pub(crate) replacement: Box<Expression>,
+}
+
+impl AtomicCmpxchg {
+	#[must_use]
+	pub const fn load_type(&self) -> LoadType {
+		self.load_type
+	}
+
+	#[must_use]
+	pub fn store_type(&self) -> StoreType {
+		self.load_type.into()
+	}
+
+	#[must_use]
+	pub const fn memory(&self) -> usize {
+		self.memory
+	}
+
+	#[must_use]
+	pub const fn offset(&self) -> u32 {
+		self.offset
+	}
+
+	#[must_use]
+	pub const fn result(&self) -> Temporary {
+		self.result
+	}
+
+	#[must_use]
+	pub const fn pointer(&self) -> &Expression {
+		&self.pointer
+	}
+
+	#[must_use]
+	pub const fn expected(&self) -> &Expression {
+		&self.expected
+	}
+
+	#[must_use]
+	pub const fn replacement(&self) -> &Expression {
+		&self.replacement
+	}
+}
+
 pub struct MemoryArgument {
 	pub(crate) memory: usize,
 	pub(crate) pointer: Box<Expression>,
@@ -1000,6 +1262,47 @@ impl MemoryFill {
 	}
 }
 
+pub struct MemoryInit {
+	pub(crate) destination: MemoryArgument,
+	pub(crate) data: usize,
+	pub(crate) offset: Box<Expression>,
+	pub(crate) size: Box<Expression>,
+}
+
+impl MemoryInit {
+	#[must_use]
+	pub const fn destination(&self) -> &MemoryArgument {
+		&self.destination
+	}
+
+	#[must_use]
+	pub const fn data(&self) -> usize {
+		self.data
+	}
+
+	#[must_use]
+	pub const fn offset(&self) -> &Expression {
+		&self.offset
+	}
+
+	#[must_use]
+	pub const fn size(&self) -> &Expression {
+		&self.size
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct DataDrop {
+	pub(crate) data: usize,
+}
+
+impl DataDrop {
+	#[must_use]
+	pub const fn data(self) -> usize {
+		self.data
+	}
+}
+
 pub enum Statement {
 	Block(Block),
 	BrIf(BrIf),
@@ -1013,10 +1316,18 @@ pub enum Statement {
 	MemoryGrow(MemoryGrow),
 	MemoryCopy(MemoryCopy),
 	MemoryFill(MemoryFill),
+	MemoryInit(MemoryInit),
+	DataDrop(DataDrop),
+	TableSet(TableSet),
+	TableGrow(TableGrow),
+	AtomicRmw(AtomicRmw),
+	AtomicCmpxchg(AtomicCmpxchg),
 }
 
 pub struct FuncData {
 	pub(crate) local_data: Vec<ValType>,
+	pub(crate) param_data: Vec<ValType>,
+	pub(crate) result_data: Vec<ValType>,
 	pub(crate) num_result: usize,
 	pub(crate) num_param: usize,
 	pub(crate) num_stack: usize,
@@ -1029,6 +1340,19 @@ impl FuncData {
 		&self.local_data
 	}
 
+	/// Returns the parameter types, in order.
+	#[must_use]
+	pub fn param_data(&self) -> &[ValType] {
+		&self.param_data
+	}
+
+	/// Returns the result types, in order. Code without a signature, such as
+	/// constant expressions, has none even though it yields a value.
+	#[must_use]
+	pub fn result_data(&self) -> &[ValType] {
+		&self.result_data
+	}
+
 	#[must_use]
 	pub const fn num_result(&self) -> usize {
 		self.num_result
@@ -1,28 +1,38 @@
 use crate::{
 	node::{
-		Align, Expression, GetGlobal, LoadAt, Local, ResultList, SetTemporary, Statement, Temporary,
+		Align, BinOp, BinOpType, Expression, GetGlobal, LoadAt, Local, MemorySize, ResultList,
+		SetTemporary, Statement, TableGet, TableSize, Temporary, UnOp, UnOpType,
 	},
 	visit::{Driver, Visitor},
 };
 
-pub struct ReadGet<A, B, C> {
+pub struct ReadGet<A, B, C, D> {
 	has_local: A,
 	has_global: B,
 	has_memory: C,
+	has_table: D,
 	result: bool,
 }
 
-impl<A, B, C> ReadGet<A, B, C>
+impl<A, B, C, D> ReadGet<A, B, C, D>
 where
 	A: Fn(Local) -> bool,
 	B: Fn(GetGlobal) -> bool,
-	C: Fn(&LoadAt) -> bool,
+	C: Fn(usize) -> bool,
+	D: Fn(usize) -> bool,
 {
-	pub fn run<D: Driver<Self>>(node: &D, has_local: A, has_global: B, has_memory: C) -> bool {
+	pub fn run<E: Driver<Self>>(
+		node: &E,
+		has_local: A,
+		has_global: B,
+		has_memory: C,
+		has_table: D,
+	) -> bool {
 		let mut visitor = Self {
 			has_local,
 			has_global,
 			has_memory,
+			has_table,
 			result: false,
 		};
 
@@ -32,23 +42,90 @@ where
 	}
 }
 
-impl<A, B, C> Visitor for ReadGet<A, B, C>
+impl<A, B, C, D> Visitor for ReadGet<A, B, C, D>
 where
 	A: Fn(Local) -> bool,
 	B: Fn(GetGlobal) -> bool,
-	C: Fn(&LoadAt) -> bool,
+	C: Fn(usize) -> bool,
+	D: Fn(usize) -> bool,
 {
 	fn visit_get_global(&mut self, get_global: GetGlobal) {
 		self.result |= (self.has_global)(get_global);
 	}
 
 	fn visit_load_at(&mut self, load_at: &LoadAt) {
-		self.result |= (self.has_memory)(load_at);
+		self.result |= (self.has_memory)(load_at.memory());
+	}
+
+	fn visit_memory_size(&mut self, memory_size: &MemorySize) {
+		self.result |= (self.has_memory)(memory_size.memory());
 	}
 
 	fn visit_get_local(&mut self, local: Local) {
 		self.result |= (self.has_local)(local);
 	}
+
+	fn visit_table_get(&mut self, table_get: &TableGet) {
+		self.result |= (self.has_table)(table_get.table());
+	}
+
+	fn visit_table_size(&mut self, table_size: &TableSize) {
+		self.result |= (self.has_table)(table_size.table());
+	}
+}
+
+// Reading a value can trap even when nothing else observes it, so a dropped one
+// still has to be computed when it holds any of these.
+pub struct MayTrap {
+	result: bool,
+}
+
+impl MayTrap {
+	pub fn run<E: Driver<Self>>(node: &E) -> bool {
+		let mut visitor = Self { result: false };
+
+		node.accept(&mut visitor);
+
+		visitor.result
+	}
+}
+
+impl Visitor for MayTrap {
+	fn visit_load_at(&mut self, _: &LoadAt) {
+		self.result = true;
+	}
+
+	fn visit_table_get(&mut self, _: &TableGet) {
+		self.result = true;
+	}
+
+	fn visit_un_op(&mut self, un_op: &UnOp) {
+		self.result |= matches!(
+			un_op.op_type(),
+			UnOpType::Truncate_I32_F32
+				| UnOpType::Truncate_I32_F64
+				| UnOpType::Truncate_U32_F32
+				| UnOpType::Truncate_U32_F64
+				| UnOpType::Truncate_I64_F32
+				| UnOpType::Truncate_I64_F64
+				| UnOpType::Truncate_U64_F32
+				| UnOpType::Truncate_U64_F64
+		);
+	}
+
+	fn visit_bin_op(&mut self, bin_op: &BinOp) {
+		self.result |= matches!(
+			bin_op.op_type(),
+			BinOpType::DivS_I32
+				| BinOpType::DivU_I32
+				| BinOpType::RemS_I32
+				| BinOpType::RemU_I32
+				| BinOpType::DivS_I64
+				| BinOpType::DivU_I64
+				| BinOpType::RemS_I64
+				| BinOpType::RemU_I64
+		);
+	}
 }
 
 #[derive(Default)]
@@ -82,6 +159,21 @@ impl Stack {
 		self.var_list.pop().unwrap()
 	}
 
+	pub fn peek(&self) -> Option<&Expression> {
+		self.var_list.last()
+	}
+
+	// Apply `op_type` to the value `depth` slots below the top, in place
+	pub fn wrap_un_op(&mut self, depth: usize, op_type: UnOpType) {
+		let index = self.len() - depth - 1;
+		let rhs = std::mem::replace(&mut self.var_list[index], Expression::RefNull);
+
+		self.var_list[index] = Expression::UnOp(UnOp {
+			op_type,
+			rhs: rhs.into(),
+		});
+	}
+
 	pub fn pop_len(&'_ mut self, len: usize) -> impl Iterator<Item = Expression> + '_ {
 		let desired = self.len() - len;
 
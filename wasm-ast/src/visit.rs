@@ -1,7 +1,8 @@
 use crate::node::{
-	BinOp, Block, Br, BrIf, BrTable, Call, CallIndirect, CmpOp, Expression, FuncData, GetGlobal,
-	If, LoadAt, Local, MemoryCopy, MemoryFill, MemoryGrow, MemorySize, Select, SetGlobal, SetLocal,
-	SetTemporary, Statement, StoreAt, Temporary, Terminator, UnOp, Value,
+	AtomicCmpxchg, AtomicRmw, BinOp, Block, Br, BrIf, BrTable, Call, CallIndirect, CmpOp, DataDrop,
+	Expression, FuncData, GetGlobal, If, LoadAt, Local, MemoryCopy, MemoryFill, MemoryGrow,
+	MemoryInit, MemorySize, RefFunc, Select, SetGlobal, SetLocal, SetTemporary, Statement, StoreAt,
+	TableGet, TableGrow, TableSet, TableSize, Temporary, Terminator, UnOp, Value,
 };
 
 pub trait Visitor {
@@ -13,10 +14,18 @@ pub trait Visitor {
 
 	fn visit_get_global(&mut self, _: GetGlobal) {}
 
+	fn visit_ref_func(&mut self, _: RefFunc) {}
+
+	fn visit_ref_null(&mut self) {}
+
 	fn visit_load_at(&mut self, _: &LoadAt) {}
 
 	fn visit_memory_size(&mut self, _: &MemorySize) {}
 
+	fn visit_table_get(&mut self, _: &TableGet) {}
+
+	fn visit_table_size(&mut self, _: &TableSize) {}
+
 	fn visit_value(&mut self, _: Value) {}
 
 	fn visit_un_op(&mut self, _: &UnOp) {}
@@ -59,6 +68,18 @@ pub trait Visitor {
 
 	fn visit_memory_fill(&mut self, _: &MemoryFill) {}
 
+	fn visit_memory_init(&mut self, _: &MemoryInit) {}
+
+	fn visit_data_drop(&mut self, _: DataDrop) {}
+
+	fn visit_table_set(&mut self, _: &TableSet) {}
+
+	fn visit_table_grow(&mut self, _: &TableGrow) {}
+
+	fn visit_atomic_rmw(&mut self, _: &AtomicRmw) {}
+
+	fn visit_atomic_cmpxchg(&mut self, _: &AtomicCmpxchg) {}
+
 	fn visit_statement(&mut self, _: &Statement) {}
 }
 
@@ -94,6 +115,12 @@ impl<T: Visitor> Driver<T> for GetGlobal {
 	}
 }
 
+impl<T: Visitor> Driver<T> for RefFunc {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_ref_func(*self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for LoadAt {
 	fn accept(&self, visitor: &mut T) {
 		self.pointer().accept(visitor);
@@ -108,6 +135,20 @@ impl<T: Visitor> Driver<T> for MemorySize {
 	}
 }
 
+impl<T: Visitor> Driver<T> for TableGet {
+	fn accept(&self, visitor: &mut T) {
+		self.index().accept(visitor);
+
+		visitor.visit_table_get(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableSize {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_table_size(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for MemoryCopy {
 	fn accept(&self, visitor: &mut T) {
 		self.destination().pointer().accept(visitor);
@@ -128,6 +169,22 @@ impl<T: Visitor> Driver<T> for MemoryFill {
 	}
 }
 
+impl<T: Visitor> Driver<T> for MemoryInit {
+	fn accept(&self, visitor: &mut T) {
+		self.destination().pointer().accept(visitor);
+		self.offset().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_memory_init(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for DataDrop {
+	fn accept(&self, visitor: &mut T) {
+		visitor.visit_data_drop(*self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for Value {
 	fn accept(&self, visitor: &mut T) {
 		visitor.visit_value(*self);
@@ -167,8 +224,12 @@ impl<T: Visitor> Driver<T> for Expression {
 			Self::GetTemporary(v) => v.accept(visitor),
 			Self::GetLocal(v) => v.accept(visitor),
 			Self::GetGlobal(v) => v.accept(visitor),
+			Self::RefFunc(v) => v.accept(visitor),
+			Self::RefNull => visitor.visit_ref_null(),
 			Self::LoadAt(v) => v.accept(visitor),
 			Self::MemorySize(v) => v.accept(visitor),
+			Self::TableGet(v) => v.accept(visitor),
+			Self::TableSize(v) => v.accept(visitor),
 			Self::Value(v) => v.accept(visitor),
 			Self::UnOp(v) => v.accept(visitor),
 			Self::BinOp(v) => v.accept(visitor),
@@ -199,6 +260,8 @@ impl<T: Visitor> Driver<T> for Terminator {
 			Self::Unreachable => visitor.visit_unreachable(),
 			Self::Br(v) => v.accept(visitor),
 			Self::BrTable(v) => v.accept(visitor),
+			Self::ReturnCall(v) => v.accept(visitor),
+			Self::ReturnCallIndirect(v) => v.accept(visitor),
 		}
 
 		visitor.visit_terminator(self);
@@ -303,6 +366,43 @@ impl<T: Visitor> Driver<T> for MemoryGrow {
 	}
 }
 
+impl<T: Visitor> Driver<T> for TableSet {
+	fn accept(&self, visitor: &mut T) {
+		self.index().accept(visitor);
+		self.value().accept(visitor);
+
+		visitor.visit_table_set(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for TableGrow {
+	fn accept(&self, visitor: &mut T) {
+		self.value().accept(visitor);
+		self.size().accept(visitor);
+
+		visitor.visit_table_grow(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for AtomicRmw {
+	fn accept(&self, visitor: &mut T) {
+		self.pointer().accept(visitor);
+		self.value().accept(visitor);
+
+		visitor.visit_atomic_rmw(self);
+	}
+}
+
+impl<T: Visitor> Driver<T> for AtomicCmpxchg {
+	fn accept(&self, visitor: &mut T) {
+		self.pointer().accept(visitor);
+		self.expected().accept(visitor);
+		self.replacement().accept(visitor);
+
+		visitor.visit_atomic_cmpxchg(self);
+	}
+}
+
 impl<T: Visitor> Driver<T> for Statement {
 	fn accept(&self, visitor: &mut T) {
 		match self {
@@ -318,6 +418,12 @@ impl<T: Visitor> Driver<T> for Statement {
 			Self::MemoryGrow(v) => v.accept(visitor),
 			Self::MemoryCopy(v) => v.accept(visitor),
 			Self::MemoryFill(v) => v.accept(visitor),
+			Self::MemoryInit(v) => v.accept(visitor),
+			Self::DataDrop(v) => v.accept(visitor),
+			Self::TableSet(v) => v.accept(visitor),
+			Self::TableGrow(v) => v.accept(visitor),
+			Self::AtomicRmw(v) => v.accept(visitor),
+			Self::AtomicCmpxchg(v) => v.accept(visitor),
 		}
 
 		visitor.visit_statement(self);